@@ -0,0 +1,32 @@
+// Benchmarks of the per-click paths on a large board. Compare the hashers with
+// `cargo bench` and `cargo bench --features fxhash`
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rusty_minesweeper::model::Minesweeper;
+
+const SIDE: u16 = 200;
+
+// A large board with mines only on the last row, so the first click floods
+// almost the whole board
+fn large_board() -> Minesweeper {
+    Minesweeper::from_mines(SIDE, SIDE, (0..SIDE).step_by(2).map(|x| (x, SIDE - 1)))
+}
+
+fn flood_fill(c: &mut Criterion) {
+    let board = large_board();
+    c.bench_function("flood fill", |b| {
+        b.iter(|| {
+            let mut game = board.clone();
+            game.open(black_box((0, 0)));
+            game
+        })
+    });
+}
+
+fn render(c: &mut Criterion) {
+    let mut board = large_board();
+    board.open((0, 0));
+    c.bench_function("render", |b| b.iter(|| black_box(&board).to_string()));
+}
+
+criterion_group!(benches, flood_fill, render);
+criterion_main!(benches);
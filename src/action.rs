@@ -0,0 +1,323 @@
+use crate::{
+    gesture::GestureKind,
+    model::{GameStatus, Minesweeper, Position},
+    selection::Rect,
+};
+use serde::{Deserialize, Serialize};
+
+// A single player action on the board, e.g. `{"open":[3,4]}` in JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Open(Position),
+    ToggleFlag(Position),
+    // Open a cell even if it is flagged, see `Minesweeper::force_open`
+    ForceOpen(Position),
+    // Open the neighbours of a number, see `Minesweeper::chord`
+    Chord(Position),
+    // Open a rectangle as a single move, see `Minesweeper::open_region`
+    OpenRegion(Rect),
+    // Flag, question or clear a cell, see `Minesweeper::cycle_mark`
+    CycleMark(Position),
+    // Spend a probe on a cell, see `Minesweeper::probe`
+    Probe(Position),
+}
+
+// An action with the time it was made in milliseconds, as recorded by a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimedAction {
+    pub action: Action,
+    pub time_ms: u64,
+    // The gesture which triggered the action, see `GestureResolver`
+    #[serde(default)]
+    pub gesture: Option<GestureKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchResult {
+    Applied { count: usize },
+    // Nothing was applied because the action with the index was illegal
+    RolledBack { index: usize },
+}
+
+impl Action {
+    // The position of the action, the top left corner for a rectangle
+    pub fn position(&self) -> Position {
+        match *self {
+            Action::Open(position)
+            | Action::ToggleFlag(position)
+            | Action::ForceOpen(position)
+            | Action::Chord(position)
+            | Action::CycleMark(position)
+            | Action::Probe(position) => position,
+            Action::OpenRegion(rect) => (rect.x, rect.y),
+        }
+    }
+}
+
+impl Minesweeper {
+    // Switch the primary and secondary actions, so a tap flags instead of opening.
+    // Essential on touch devices which have no secondary button
+    pub fn set_flag_mode(&mut self, flag_mode: bool) {
+        self.flag_mode = flag_mode;
+    }
+
+    pub fn flag_mode(&self) -> bool {
+        self.flag_mode
+    }
+
+    // The action of a click or tap on the position
+    pub fn primary_action(&self, position: Position) -> Action {
+        if self.flag_mode {
+            Action::ToggleFlag(position)
+        } else {
+            Action::Open(position)
+        }
+    }
+
+    // The action of a right click or long press on the position
+    pub fn secondary_action(&self, position: Position) -> Action {
+        if self.flag_mode {
+            Action::Open(position)
+        } else {
+            Action::ToggleFlag(position)
+        }
+    }
+
+    // Every action that changes the board right now, in row-major order. Bots enumerate
+    // them instead of reimplementing the rules. Rectangles are left out, they only combine
+    // opens. Nothing is legal once the game is over
+    pub fn legal_actions(&self) -> Vec<Action> {
+        if self.status() != GameStatus::InProgress {
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let position = (x, y);
+                if !self.chord_targets(position).is_empty() {
+                    actions.push(Action::Chord(position));
+                }
+                if self.open_positions.contains(&position) || self.is_locked(position) {
+                    continue;
+                }
+                if self.flagged_positions.contains(&position) {
+                    actions.push(Action::ToggleFlag(position));
+                    actions.push(Action::CycleMark(position));
+                    actions.push(Action::ForceOpen(position));
+                } else {
+                    actions.push(Action::Open(position));
+                    if self.has_flags_left() {
+                        actions.push(Action::ToggleFlag(position));
+                    }
+                    actions.push(Action::CycleMark(position));
+                }
+                if self.probes_left() > 0 {
+                    actions.push(Action::Probe(position));
+                }
+            }
+        }
+        actions
+    }
+
+    // Apply recorded actions as one step. Either every action is applied or, if one of
+    // them is illegal, the game is left untouched. An action is illegal if it doesn't
+    // make a move or was made earlier than the action before it
+    pub fn apply_actions(&mut self, actions: &[TimedAction]) -> BatchResult {
+        let mut game = self.clone();
+        let mut last_time_ms = 0;
+        for (index, timed) in actions.iter().enumerate() {
+            let moves = game.moves();
+            if timed.time_ms < last_time_ms || game.apply(timed.action).moves() == moves {
+                return BatchResult::RolledBack { index };
+            }
+            last_time_ms = timed.time_ms;
+        }
+        *self = game;
+        BatchResult::Applied {
+            count: actions.len(),
+        }
+    }
+
+    pub fn apply(&mut self, action: Action) -> &mut Self {
+        match action {
+            Action::Open(position) => {
+                self.open(position);
+            }
+            Action::ToggleFlag(position) => {
+                self.toggle_flag(position);
+            }
+            Action::ForceOpen(position) => {
+                self.force_open(position);
+            }
+            Action::Chord(position) => {
+                self.chord(position);
+            }
+            Action::OpenRegion(rect) => {
+                let _ = self.open_region(rect);
+            }
+            Action::CycleMark(position) => {
+                self.cycle_mark(position);
+            }
+            Action::Probe(position) => {
+                self.probe(position);
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper
+            .apply(Action::ToggleFlag((0, 0)))
+            .apply(Action::Open((1, 0)));
+        assert!(minesweeper.flagged_positions.contains(&(0, 0)));
+        assert_eq!(minesweeper.open_positions.len(), 1, "1 cell is opened");
+        assert_eq!(minesweeper.moves(), 2, "2 moves made");
+    }
+
+    #[test]
+    fn test_apply_actions() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        let timed = |action, time_ms| TimedAction {
+            action,
+            time_ms,
+            gesture: None,
+        };
+
+        // ================================================
+        // Test that a valid batch is applied
+        let result = minesweeper.apply_actions(&[
+            timed(Action::ToggleFlag((0, 0)), 100),
+            timed(Action::Open((1, 0)), 250),
+        ]);
+        assert_eq!(result, BatchResult::Applied { count: 2 });
+        assert_eq!(minesweeper.moves(), 2, "2 moves made");
+
+        // ================================================
+        // Test that an illegal action rolls back the whole batch
+        let before = minesweeper.clone();
+        let result = minesweeper.apply_actions(&[
+            timed(Action::Open((2, 0)), 300),
+            timed(Action::Open((1, 0)), 400),
+        ]);
+        assert_eq!(result, BatchResult::RolledBack { index: 1 }, "Already open");
+        assert_eq!(minesweeper.moves(), before.moves(), "Nothing applied");
+        assert_eq!(minesweeper.open_positions, before.open_positions);
+
+        let result = minesweeper.apply_actions(&[
+            timed(Action::Open((2, 0)), 300),
+            timed(Action::Open((0, 1)), 200),
+        ]);
+        assert_eq!(result, BatchResult::RolledBack { index: 1 }, "Out of order");
+        assert_eq!(minesweeper.moves(), before.moves(), "Nothing applied");
+    }
+
+    #[test]
+    fn test_action_json() {
+        let timed = TimedAction {
+            action: Action::ForceOpen((3, 4)),
+            time_ms: 20,
+            gesture: Some(GestureKind::LongPress),
+        };
+        let json = r#"{"action":{"force_open":[3,4]},"time_ms":20,"gesture":"long_press"}"#;
+        assert_eq!(serde_json::to_string(&timed).unwrap(), json);
+        assert_eq!(serde_json::from_str::<TimedAction>(json).unwrap(), timed);
+        let json = r#"{"action":{"force_open":[3,4]},"time_ms":20}"#;
+        assert_eq!(
+            serde_json::from_str::<TimedAction>(json).unwrap().gesture,
+            None,
+            "No gesture"
+        );
+        assert_eq!(
+            serde_json::to_string(&BatchResult::RolledBack { index: 2 }).unwrap(),
+            r#"{"status":"rolled_back","index":2}"#
+        );
+    }
+
+    #[test]
+    fn test_legal_actions() {
+        // The flag around the 1 is wrong, but chording it is still legal
+        let mut minesweeper = crate::board!["*o", "f."];
+        assert_eq!(
+            minesweeper.legal_actions(),
+            vec![
+                Action::Open((0, 0)),
+                Action::ToggleFlag((0, 0)),
+                Action::CycleMark((0, 0)),
+                Action::Chord((1, 0)),
+                Action::ToggleFlag((0, 1)),
+                Action::CycleMark((0, 1)),
+                Action::ForceOpen((0, 1)),
+                Action::Open((1, 1)),
+                Action::ToggleFlag((1, 1)),
+                Action::CycleMark((1, 1)),
+            ]
+        );
+
+        // ================================================
+        // Test that the mark can still be cycled without flags left
+        let mut capped = crate::board!["*o", "f."];
+        capped.set_flag_cap(true);
+        assert_eq!(
+            capped.legal_actions()[6..],
+            [Action::Open((1, 1)), Action::CycleMark((1, 1))],
+            "No flag left"
+        );
+
+        // ================================================
+        // Test that probes are legal while there are some left
+        capped.set_probes(1);
+        assert_eq!(
+            capped.legal_actions()[8..],
+            [
+                Action::Open((1, 1)),
+                Action::CycleMark((1, 1)),
+                Action::Probe((1, 1))
+            ]
+        );
+
+        // ================================================
+        // Test that every legal action makes a move
+        for action in minesweeper.legal_actions() {
+            let mut game = minesweeper.clone();
+            game.apply(action);
+            assert_eq!(game.moves(), 1, "{action:?} is a move");
+        }
+
+        // ================================================
+        // Test that nothing is legal after the game
+        minesweeper.open((0, 0));
+        assert_eq!(minesweeper.legal_actions(), vec![], "Game is lost");
+    }
+
+    #[test]
+    fn test_flag_mode() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        assert_eq!(minesweeper.primary_action((0, 0)), Action::Open((0, 0)));
+        assert_eq!(
+            minesweeper.secondary_action((0, 0)),
+            Action::ToggleFlag((0, 0))
+        );
+
+        // ================================================
+        // Test that flag mode switches the actions
+        minesweeper.set_flag_mode(true);
+        assert!(minesweeper.flag_mode());
+        assert_eq!(
+            minesweeper.primary_action((0, 0)),
+            Action::ToggleFlag((0, 0))
+        );
+        assert_eq!(minesweeper.secondary_action((0, 0)), Action::Open((0, 0)));
+        minesweeper.apply(minesweeper.primary_action((0, 0)));
+        assert!(minesweeper.flagged_positions.contains(&(0, 0)));
+    }
+}
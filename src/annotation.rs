@@ -0,0 +1,86 @@
+// Free-form notes on cells, e.g. a color name or "1-2-1". Players and tutorial authors
+// mark their reasoning with them, and they never affect the rules
+use crate::model::{Minesweeper, Position};
+use serde::{Deserialize, Serialize};
+
+// Longest tag in characters, enough for a short label
+pub const MAX_TAG_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub position: Position,
+    pub tag: String,
+}
+
+impl Minesweeper {
+    // Tag a cell, or remove its tag with an empty one. Return false if the cell is outside
+    // the board or the tag is too long
+    pub fn annotate(&mut self, position: Position, tag: &str) -> bool {
+        let (x, y) = position;
+        if x >= self.width() || y >= self.height() || tag.chars().count() > MAX_TAG_LENGTH {
+            return false;
+        }
+        if tag.is_empty() {
+            self.annotations.remove(&position);
+        } else {
+            self.annotations.insert(position, tag.to_string());
+        }
+        true
+    }
+
+    pub fn annotation(&self, position: Position) -> Option<&str> {
+        self.annotations.get(&position).map(String::as_str)
+    }
+
+    // All tagged cells sorted by x, then y
+    pub fn annotations(&self) -> Vec<Annotation> {
+        let mut annotations: Vec<Annotation> = self
+            .annotations
+            .iter()
+            .map(|(&position, tag)| Annotation {
+                position,
+                tag: tag.clone(),
+            })
+            .collect();
+        annotations.sort_by_key(|annotation| annotation.position);
+        annotations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::CellState;
+
+    #[test]
+    fn test_annotate() {
+        let mut minesweeper = crate::board!["*..", "...", "..."];
+
+        // ================================================
+        // Test that tags are set, replaced and removed
+        assert!(minesweeper.annotate((2, 0), "1-2-1"));
+        assert!(minesweeper.annotate((0, 1), "red"));
+        assert!(minesweeper.annotate((0, 1), "blue"));
+        assert_eq!(minesweeper.annotation((0, 1)), Some("blue"));
+        let positions: Vec<_> = minesweeper
+            .annotations()
+            .into_iter()
+            .map(|annotation| annotation.position)
+            .collect();
+        assert_eq!(positions, vec![(0, 1), (2, 0)], "Sorted by position");
+        assert!(minesweeper.annotate((0, 1), ""));
+        assert_eq!(minesweeper.annotation((0, 1)), None, "Tag removed");
+
+        // ================================================
+        // Test invalid annotations
+        assert!(!minesweeper.annotate((3, 0), "red"), "Outside the board");
+        assert!(!minesweeper.annotate((0, 0), &"x".repeat(33)), "Too long");
+        assert_eq!(minesweeper.annotations().len(), 1);
+
+        // ================================================
+        // Test that tags don't affect the rules
+        assert!(minesweeper.annotate((0, 0), "mine?"));
+        minesweeper.open((0, 0));
+        assert_eq!(minesweeper.cell_state((0, 0)), CellState::Exploded);
+        assert_eq!(minesweeper.moves(), 1, "Tags aren't moves");
+    }
+}
@@ -0,0 +1,153 @@
+// `Arbitrary` implementations for property tests and fuzzing, available with the
+// `arbitrary` feature. Boards are kept small so that generated cases stay fast
+use crate::{
+    action::Action,
+    model::{Minesweeper, Position},
+    selection::Rect,
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+pub const MAX_SIDE: u16 = 16;
+pub const MAX_ACTIONS: usize = 64;
+
+// Board size and mine layout. Unlike `Minesweeper::new`, the layout comes from the
+// input bytes, so every generated case is reproducible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardConfig {
+    pub width: u16,
+    pub height: u16,
+    pub mines: Vec<Position>,
+}
+
+impl BoardConfig {
+    pub fn build(&self) -> Minesweeper {
+        Minesweeper::from_mines(self.width, self.height, self.mines.iter().copied())
+    }
+
+    fn arbitrary_position(&self, u: &mut Unstructured<'_>) -> Result<Position> {
+        Ok((
+            u.int_in_range(0..=self.width - 1)?,
+            u.int_in_range(0..=self.height - 1)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for BoardConfig {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let width = u.int_in_range(1..=MAX_SIDE)?;
+        let height = u.int_in_range(1..=MAX_SIDE)?;
+        let mut config = BoardConfig {
+            width,
+            height,
+            mines: Vec::new(),
+        };
+
+        // Leave at least one cell without a mine
+        let mines_count = u.int_in_range(0..=(width * height - 1) as usize)?;
+        while config.mines.len() < mines_count {
+            let position = config.arbitrary_position(u)?;
+            if !config.mines.contains(&position) {
+                config.mines.push(position);
+            } else if u.is_empty() {
+                break;
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// A board and a sequence of actions with positions on that board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    pub board: BoardConfig,
+    pub actions: Vec<Action>,
+}
+
+impl Scenario {
+    // Build the board and apply all actions to it
+    pub fn play(&self) -> Minesweeper {
+        let mut minesweeper = self.board.build();
+        self.actions.iter().for_each(|&action| {
+            minesweeper.apply(action);
+        });
+        minesweeper
+    }
+}
+
+impl<'a> Arbitrary<'a> for Scenario {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let board = BoardConfig::arbitrary(u)?;
+        let actions_count = u.int_in_range(0..=MAX_ACTIONS)?;
+        let actions = (0..actions_count)
+            .map(|_| {
+                let position = board.arbitrary_position(u)?;
+                Ok(match u.int_in_range(0..=7)? {
+                    0 | 1 => Action::ToggleFlag(position),
+                    2 => Action::ForceOpen(position),
+                    3 => Action::Chord(position),
+                    4 => Action::OpenRegion(Rect {
+                        x: position.0,
+                        y: position.1,
+                        width: u.int_in_range(1..=3)?,
+                        height: u.int_in_range(1..=3)?,
+                    }),
+                    5 => Action::CycleMark(position),
+                    _ => Action::Open(position),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Scenario { board, actions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    // Run the check on scenarios generated from random bytes
+    fn check_scenarios(check: impl Fn(&Scenario, &Minesweeper)) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let bytes: Vec<u8> = (0..512).map(|_| rng.gen()).collect();
+            let scenario = Scenario::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            let minesweeper = scenario.play();
+            check(&scenario, &minesweeper);
+        }
+    }
+
+    #[test]
+    fn test_board_config() {
+        check_scenarios(|scenario, minesweeper| {
+            let cells = scenario.board.width as usize * scenario.board.height as usize;
+            assert!(scenario.board.mines.len() < cells, "At least 1 safe cell");
+            assert_eq!(minesweeper.mines.len(), scenario.board.mines.len());
+        });
+    }
+
+    #[test]
+    fn test_cell_count_invariant() {
+        check_scenarios(|scenario, minesweeper| {
+            let cells = scenario.board.width as usize * scenario.board.height as usize;
+            let hidden = (0..scenario.board.width)
+                .flat_map(|x| (0..scenario.board.height).map(move |y| (x, y)))
+                .filter(|position| {
+                    !minesweeper.open_positions.contains(position)
+                        && !minesweeper.flagged_positions.contains(position)
+                })
+                .count();
+            assert_eq!(
+                minesweeper.open_positions.len() + minesweeper.flagged_positions.len() + hidden,
+                cells,
+                "Open, flagged and hidden cells add up to the board: {scenario:?}"
+            );
+            assert!(
+                minesweeper
+                    .open_positions
+                    .is_disjoint(&minesweeper.flagged_positions),
+                "No cell is both open and flagged: {scenario:?}"
+            );
+        });
+    }
+}
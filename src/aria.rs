@@ -0,0 +1,125 @@
+use crate::{
+    model::{CellState, Minesweeper},
+    narration::{Language, Message},
+};
+use serde::Serialize;
+
+// A board shaped for accessible rendering: a grid of cells with ARIA roles and labels
+#[derive(Debug, Serialize)]
+pub struct AriaGrid {
+    pub role: &'static str,
+    pub label: String,
+    pub rows: Vec<AriaRow>,
+    // Message for an ARIA live region, if the game has something to announce
+    pub live_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AriaRow {
+    pub role: &'static str,
+    pub cells: Vec<AriaCell>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AriaCell {
+    pub role: &'static str,
+    pub x: u16,
+    pub y: u16,
+    pub label: String,
+}
+
+impl AriaGrid {
+    pub fn new(minesweeper: &Minesweeper) -> Self {
+        Self::localized(minesweeper, Language::English)
+    }
+
+    // The grid with labels and messages in the given language
+    pub fn localized(minesweeper: &Minesweeper, language: Language) -> Self {
+        let (width, height) = (minesweeper.width(), minesweeper.height());
+
+        let rows = (0..height)
+            .map(|y| AriaRow {
+                role: "row",
+                cells: (0..width)
+                    .map(|x| AriaCell {
+                        role: "gridcell",
+                        x,
+                        y,
+                        label: language.narrate(Message::Cell(minesweeper.cell_state((x, y)))),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            role: "grid",
+            label: language.narrate(Message::Board { width, height }),
+            rows,
+            live_message: minesweeper
+                .game_over
+                .then(|| language.narrate(Message::GameOver)),
+        }
+    }
+}
+
+pub(crate) fn cell_label(cell_state: CellState) -> String {
+    Language::English.narrate(Message::Cell(cell_state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_label() {
+        assert_eq!(cell_label(CellState::Hidden), "hidden");
+        assert_eq!(cell_label(CellState::Flagged), "flag");
+        assert_eq!(cell_label(CellState::Open(0)), "no adjacent mines");
+        assert_eq!(cell_label(CellState::Open(1)), "1 adjacent mine");
+        assert_eq!(cell_label(CellState::Open(3)), "3 adjacent mines");
+        assert_eq!(cell_label(CellState::Exploded), "exploded mine");
+    }
+
+    #[test]
+    fn test_new() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        // Remove all mines
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Insert 2 mines next to the cell (5, 5)
+        minesweeper.mines.insert((5, 4));
+        minesweeper.mines.insert((4, 4));
+        minesweeper.open((5, 5));
+        minesweeper.toggle_flag((4, 4));
+
+        // ================================================
+        // Test the grid of a game in progress
+        let grid = AriaGrid::new(&minesweeper);
+        assert_eq!(grid.role, "grid");
+        assert_eq!(grid.rows.len(), 10, "The grid has 10 rows");
+        assert!(grid.rows.iter().all(|row| row.cells.len() == 10));
+        assert_eq!(grid.rows[5].cells[5].label, "2 adjacent mines");
+        assert_eq!(grid.rows[4].cells[4].label, "flag");
+        assert_eq!(grid.rows[0].cells[0].label, "hidden");
+        assert_eq!((grid.rows[3].cells[7].x, grid.rows[3].cells[7].y), (7, 3));
+        assert_eq!(grid.live_message, None, "Nothing to announce");
+
+        // ================================================
+        // Test the grid of a lost game
+        minesweeper.open((5, 4));
+        let grid = AriaGrid::new(&minesweeper);
+        assert_eq!(grid.rows[4].cells[5].label, "exploded mine");
+        assert_eq!(grid.rows[4].cells[4].label, "mine");
+        assert!(grid.live_message.is_some(), "Game over is announced");
+
+        // ================================================
+        // Test the grid in another language
+        let grid = AriaGrid::localized(&minesweeper, Language::Russian);
+        assert_eq!(grid.label, "Поле сапёра, столбцов: 10, строк: 10");
+        assert_eq!(grid.rows[5].cells[5].label, "2 мины рядом");
+        assert_eq!(
+            grid.live_message.as_deref(),
+            Some("Мина взорвалась. Игра окончена.")
+        );
+    }
+}
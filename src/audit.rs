@@ -0,0 +1,149 @@
+use crate::model::{Minesweeper, Position};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantViolation {
+    MineOutOfBounds(Position),
+    OpenOutOfBounds(Position),
+    FlagOutOfBounds(Position),
+    OpenAndFlagged(Position),
+    QuestionOutOfBounds(Position),
+    // A question mark on an open or flagged cell
+    MisplacedQuestion(Position),
+    // Every cell is a mine, so the game can't be won
+    NoSafeCells,
+    // A mine is open but the game is not over
+    OpenMineInProgress(Position),
+    // The game is over but no mine is open
+    GameOverWithoutExplosion,
+}
+
+impl Minesweeper {
+    // Verify the internal consistency of the game. Runs after every action in debug
+    // builds and should be called on games restored from untrusted data
+    pub fn audit(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut violations = Vec::new();
+        let is_on_board = |&(x, y): &Position| x < self.width() && y < self.height();
+
+        let mut positions: Vec<Position> = self.mines.iter().collect();
+        positions.sort();
+        positions
+            .iter()
+            .filter(|position| !is_on_board(position))
+            .for_each(|&position| violations.push(InvariantViolation::MineOutOfBounds(position)));
+
+        sorted(&self.open_positions)
+            .into_iter()
+            .filter(|position| !is_on_board(position))
+            .for_each(|position| violations.push(InvariantViolation::OpenOutOfBounds(position)));
+
+        sorted(&self.flagged_positions)
+            .into_iter()
+            .for_each(|position| {
+                if !is_on_board(&position) {
+                    violations.push(InvariantViolation::FlagOutOfBounds(position));
+                }
+                if self.open_positions.contains(&position) {
+                    violations.push(InvariantViolation::OpenAndFlagged(position));
+                }
+            });
+
+        sorted(&self.questioned_positions)
+            .into_iter()
+            .for_each(|position| {
+                if !is_on_board(&position) {
+                    violations.push(InvariantViolation::QuestionOutOfBounds(position));
+                }
+                if self.open_positions.contains(&position)
+                    || self.flagged_positions.contains(&position)
+                {
+                    violations.push(InvariantViolation::MisplacedQuestion(position));
+                }
+            });
+
+        if self.mines.len() >= self.width() as usize * self.height() as usize {
+            violations.push(InvariantViolation::NoSafeCells);
+        }
+
+        let open_mines: Vec<Position> = positions
+            .into_iter()
+            .filter(|position| self.open_positions.contains(position))
+            .collect();
+        if self.game_over && open_mines.is_empty() {
+            violations.push(InvariantViolation::GameOverWithoutExplosion);
+        }
+        if !self.game_over {
+            open_mines.into_iter().for_each(|position| {
+                violations.push(InvariantViolation::OpenMineInProgress(position))
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+// Sort positions to report violations in a stable order
+fn sorted<'a>(positions: impl IntoIterator<Item = &'a Position>) -> Vec<Position> {
+    let mut positions: Vec<Position> = positions.into_iter().copied().collect();
+    positions.sort();
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit() {
+        // ================================================
+        // Test a consistent game
+        let mut minesweeper = crate::board!["*..", ".f.", "..o"];
+        assert_eq!(minesweeper.audit(), Ok(()));
+        minesweeper.open((0, 0));
+        assert_eq!(minesweeper.audit(), Ok(()), "Lost game is consistent");
+
+        // ================================================
+        // Test positions outside the board and cells both open and flagged
+        let mut minesweeper = crate::board!["*..", "...", "..."];
+        minesweeper.mines.insert((3, 0));
+        minesweeper.open_positions.insert((0, 3));
+        minesweeper.flagged_positions.insert((5, 5));
+        minesweeper.open_positions.insert((1, 1));
+        minesweeper.flagged_positions.insert((1, 1));
+        assert_eq!(
+            minesweeper.audit(),
+            Err(vec![
+                InvariantViolation::MineOutOfBounds((3, 0)),
+                InvariantViolation::OpenOutOfBounds((0, 3)),
+                InvariantViolation::OpenAndFlagged((1, 1)),
+                InvariantViolation::FlagOutOfBounds((5, 5)),
+            ])
+        );
+
+        // ================================================
+        // Test an incoherent status
+        let mut minesweeper = crate::board!["*.", ".."];
+        minesweeper.open_positions.insert((0, 0));
+        assert_eq!(
+            minesweeper.audit(),
+            Err(vec![InvariantViolation::OpenMineInProgress((0, 0))])
+        );
+        let mut minesweeper = crate::board!["*.", ".."];
+        minesweeper.game_over = true;
+        assert_eq!(
+            minesweeper.audit(),
+            Err(vec![InvariantViolation::GameOverWithoutExplosion])
+        );
+
+        // ================================================
+        // Test a board full of mines
+        let minesweeper = crate::board!["**"];
+        assert_eq!(
+            minesweeper.audit(),
+            Err(vec![InvariantViolation::NoSafeCells])
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// Headless entry point of the engine for servers and serverless WASI runtimes, which
+// can't call the JS bindings. Input comes from the arguments and stdin, JSON goes to
+// stdout and errors to stderr:
+//
+//     minesweeper-headless generate <width> <height> <mines> <seed>
+//     minesweeper-headless validate < submission.json
+//
+// `generate` prints a saved game, which `loadGame` restores in the browser. `validate`
+// replays a submitted game and prints the verdict of `validate_submission`.
+use rusty_minesweeper::{
+    action::TimedAction, fairness::BoardReveal, model::Minesweeper, validation::validate_submission,
+};
+use serde::Deserialize;
+use std::{io::Read, process::ExitCode, str::FromStr};
+
+const USAGE: &str = "usage: minesweeper-headless generate <width> <height> <mines> <seed>
+       minesweeper-headless validate < submission.json";
+
+// A game submitted to a leaderboard, the arguments of `validate_submission`
+#[derive(Debug, Deserialize)]
+struct Submission {
+    commitment: String,
+    reveal: BoardReveal,
+    replay: Vec<TimedAction>,
+    claimed_time_ms: u64,
+}
+
+fn number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number: {value}"))
+}
+
+fn run(args: &[String], input: impl Read) -> Result<String, String> {
+    match args {
+        [command, width, height, mines, seed] if command == "generate" => {
+            let (width, height, mines) = (number(width)?, number(height)?, number(mines)?);
+            if !Minesweeper::are_valid_parameters(width, height, mines) {
+                return Err("invalid board parameters".to_string());
+            }
+            Ok(Minesweeper::new_with_seed(width, height, mines, number(seed)?).serialize())
+        }
+        [command] if command == "validate" => {
+            let submission: Submission = serde_json::from_reader(input)
+                .map_err(|error| format!("invalid submission: {error}"))?;
+            let verdict = validate_submission(
+                &submission.commitment,
+                &submission.reveal,
+                &submission.replay,
+                submission.claimed_time_ms,
+            );
+            Ok(serde_json::to_string(&verdict).unwrap_or_default())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args, std::io::stdin().lock()) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_minesweeper::action::Action;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let save = run(&args("generate 9 9 10 42"), std::io::empty()).unwrap();
+        let game = Minesweeper::deserialize(&save).unwrap();
+        assert_eq!((game.width(), game.height()), (9, 9));
+        assert_eq!(game.seed(), Some(42));
+        assert_eq!(
+            run(&args("generate 9 9 10 42"), std::io::empty()),
+            Ok(save),
+            "Same seed, same board"
+        );
+
+        // ================================================
+        // Test that invalid input is refused
+        assert!(run(&args("generate 3 3 9 42"), std::io::empty()).is_err());
+        assert!(run(&args("generate 9 9 ten 42"), std::io::empty()).is_err());
+        assert!(run(&args("generate 9 9 10"), std::io::empty()).is_err());
+        assert_eq!(run(&[], std::io::empty()), Err(USAGE.to_string()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut game = Minesweeper::from_mines(2, 1, [(0, 0)]);
+        let commitment = game.commit();
+        game.open((1, 0));
+        let submission = serde_json::json!({
+            "commitment": commitment,
+            "reveal": game.reveal().unwrap(),
+            "replay": [TimedAction {
+                action: Action::Open((1, 0)),
+                time_ms: 100,
+                gesture: None,
+            }],
+            "claimed_time_ms": 1_000,
+        })
+        .to_string();
+        assert_eq!(
+            run(&args("validate"), submission.as_bytes()),
+            Ok(r#"{"verdict":"accepted","moves":1,"time_ms":1000}"#.to_string())
+        );
+        assert!(run(&args("validate"), "{}".as_bytes()).is_err(), "Invalid");
+    }
+}
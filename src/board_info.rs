@@ -0,0 +1,69 @@
+use crate::model::{GameStatus, Minesweeper};
+use serde::Serialize;
+
+// Everything a frontend needs to know to lay out the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardInfo {
+    pub width: u16,
+    pub height: u16,
+    pub mines: usize,
+    pub status: GameStatus,
+    // The seed to reproduce the board, see `Minesweeper::new_with_seed`. Seeds above 2^53
+    // lose precision as JS numbers, `getSeed` returns it exactly
+    pub seed: Option<u64>,
+    // Variant flags
+    pub blind_mode: Option<u32>,
+    pub flag_mode: bool,
+    pub flag_cap: bool,
+    pub safe_first_click: bool,
+    pub probes_left: u32,
+}
+
+impl BoardInfo {
+    pub fn new(minesweeper: &Minesweeper) -> Self {
+        Self {
+            width: minesweeper.width(),
+            height: minesweeper.height(),
+            mines: minesweeper.mines.len(),
+            status: minesweeper.status(),
+            seed: minesweeper.seed(),
+            blind_mode: minesweeper.blind_mode(),
+            flag_mode: minesweeper.flag_mode(),
+            flag_cap: minesweeper.flag_cap(),
+            safe_first_click: minesweeper.safe_first_click(),
+            probes_left: minesweeper.probes_left(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let mut minesweeper = Minesweeper::new_with_seed(16, 8, 20, 7);
+        minesweeper.set_blind_mode(Some(3));
+        minesweeper.set_flag_cap(true);
+        let info = BoardInfo::new(&minesweeper);
+        assert_eq!(
+            info,
+            BoardInfo {
+                width: 16,
+                height: 8,
+                mines: 20,
+                status: GameStatus::InProgress,
+                seed: Some(7),
+                blind_mode: Some(3),
+                flag_mode: false,
+                flag_cap: true,
+                safe_first_click: true,
+                probes_left: 0,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&info).unwrap(),
+            r#"{"width":16,"height":8,"mines":20,"status":"in_progress","seed":7,"blind_mode":3,"flag_mode":false,"flag_cap":true,"safe_first_click":true,"probes_left":0}"#
+        );
+    }
+}
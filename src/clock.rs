@@ -0,0 +1,131 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+// Source of time for everything time-dependent in the game. Times are milliseconds
+// measured from an arbitrary starting point, so only differences between them matter
+pub trait Clock: Debug {
+    fn now_ms(&self) -> u64;
+}
+
+// Clock of a game, which its copies share
+pub type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+// Lets a clock be shared by reference, e.g. a mock clock advanced by the test
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+}
+
+// Monotonic clock of the platform: `performance.now()` in the browser and
+// `std::time::Instant` everywhere else, including WASI runtimes
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    start: std::time::Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod performance {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        // `performance` is available both in windows and in workers
+        #[wasm_bindgen(js_namespace = performance)]
+        pub fn now() -> f64;
+    }
+}
+
+impl Clock for SystemClock {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn now_ms(&self) -> u64 {
+        performance::now() as u64
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn now_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+// Clock which only moves when told to, for deterministic tests
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    pub fn new(now_ms: u64) -> Self {
+        Self {
+            now: AtomicU64::new(now_ms),
+        }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now.store(now_ms, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, ms: u64) {
+        self.now.fetch_add(ms, Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock() {
+        let clock = SystemClock::new();
+        let start = clock.now_ms();
+        assert!(start < 1_000, "Counted from the creation of the clock");
+        assert!(clock.now_ms() >= start, "Time never goes back");
+    }
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = Arc::new(MockClock::new(1_000));
+        // The clock can be shared with the code under test as a trait object
+        let shared: SharedClock = clock.clone();
+        assert_eq!(shared.now_ms(), 1_000);
+
+        clock.advance(250);
+        assert_eq!(shared.now_ms(), 1_250, "Time advanced by 250 ms");
+
+        clock.set(10);
+        assert_eq!(shared.now_ms(), 10, "Time set to 10 ms");
+    }
+}
@@ -0,0 +1,508 @@
+// Compact binary encoding of replays and move histories for storage and upload.
+//
+// Every entry is an action kind, a position, a number and a gesture. The number is the
+// milliseconds since the previous action in replays, the count of opened cells in
+// histories. The gesture is the one which triggered the action in replays and is never
+// set in histories. Positions are stored as the difference to the previous position, so
+// clicks close together take a byte each. Consecutive entries with the same kind,
+// position difference, number and gesture form a run stored once with its length, which
+// packs a row flagged at a steady pace.
+// A cascade is stored as its click and a count, since replaying the click opens the same
+// cells again.
+//
+// Layout: a version byte, then the runs. A run is a header byte with the kind in the three
+// lowest bits and the run length minus one in the others, followed by the zigzag varints
+// of the x and y differences, the varints of the width and height for rectangles, the
+// varint of the number and a gesture byte. Version 2 had no gesture byte, version 1 had
+// no rectangles either and two bits for the kind, both are still decoded.
+use crate::{
+    action::{Action, TimedAction},
+    gesture::GestureKind,
+    model::{Minesweeper, Position},
+    selection::Rect,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+pub const ENCODING_VERSION: u8 = 3;
+
+const KIND_BITS: u8 = 3;
+const MAX_RUN: usize = 1 << (8 - KIND_BITS);
+const OPEN_REGION: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    UnsupportedVersion(u8),
+    // The data ends in the middle of a run
+    Truncated,
+    // A position or a number doesn't fit its type
+    Overflow,
+    UnknownKind(u8),
+    UnknownGesture(u8),
+    // Replaying the move with the index opened a different number of cells, so the game
+    // isn't the one the history started from
+    Mismatch { index: usize },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported encoding version {version}")
+            }
+            DecodeError::Truncated => write!(f, "truncated data"),
+            DecodeError::Overflow => write!(f, "value out of range"),
+            DecodeError::UnknownKind(kind) => write!(f, "unknown action kind {kind}"),
+            DecodeError::UnknownGesture(gesture) => write!(f, "unknown gesture {gesture}"),
+            DecodeError::Mismatch { index } => write!(f, "move {index} doesn't match the game"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    action: Action,
+    number: u64,
+    gesture: Option<GestureKind>,
+}
+
+pub fn encode_replay(replay: &[TimedAction]) -> Vec<u8> {
+    let mut last_time_ms = 0;
+    let entries = replay.iter().map(|timed| {
+        // Replays are in time order, a wrong order is stored as no time passing
+        let number = timed.time_ms.saturating_sub(last_time_ms);
+        last_time_ms = timed.time_ms;
+        Entry {
+            action: timed.action,
+            number,
+            gesture: timed.gesture,
+        }
+    });
+    encode(entries)
+}
+
+pub fn decode_replay(data: &[u8]) -> Result<Vec<TimedAction>, DecodeError> {
+    let mut time_ms: u64 = 0;
+    decode(data)?
+        .into_iter()
+        .map(|entry| {
+            time_ms = time_ms
+                .checked_add(entry.number)
+                .ok_or(DecodeError::Overflow)?;
+            Ok(TimedAction {
+                action: entry.action,
+                time_ms,
+                gesture: entry.gesture,
+            })
+        })
+        .collect()
+}
+
+impl Minesweeper {
+    // The moves which can be undone, encoded as their actions and opened cell counts
+    pub fn encode_history(&self) -> Vec<u8> {
+        encode(self.history().iter().map(|recorded| Entry {
+            action: recorded.action,
+            number: recorded.opened().len() as u64,
+            gesture: None,
+        }))
+    }
+
+    // Make the moves of an encoded history. The game must be in the state the history
+    // started from, e.g. a new game with the same seed. On a mismatch the moves before
+    // it stay made
+    pub fn replay_history(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        for (index, entry) in decode(data)?.into_iter().enumerate() {
+            let moves = self.moves();
+            self.apply(entry.action);
+            let opened = self.history().last().map(|last| last.opened().len() as u64);
+            if self.moves() == moves || opened != Some(entry.number) {
+                return Err(DecodeError::Mismatch { index });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn encode(entries: impl IntoIterator<Item = Entry>) -> Vec<u8> {
+    let mut data = vec![ENCODING_VERSION];
+    let mut last_position = (0, 0);
+    // The first entry of the current run, its differences and its length
+    let mut run: Option<(Entry, (i64, i64), usize)> = None;
+    for entry in entries {
+        let position = entry.action.position();
+        let delta = difference(position, last_position);
+        last_position = position;
+        match &mut run {
+            Some((first, first_delta, length))
+                if kind(first.action) == kind(entry.action)
+                    && size(first.action) == size(entry.action)
+                    && first.number == entry.number
+                    && first.gesture == entry.gesture
+                    && *first_delta == delta
+                    && *length < MAX_RUN =>
+            {
+                *length += 1;
+            }
+            _ => {
+                if let Some(run) = run {
+                    write_run(&mut data, run);
+                }
+                run = Some((entry, delta, 1));
+            }
+        }
+    }
+    if let Some(run) = run {
+        write_run(&mut data, run);
+    }
+    data
+}
+
+fn decode(data: &[u8]) -> Result<Vec<Entry>, DecodeError> {
+    let (&version, mut data) = data.split_first().ok_or(DecodeError::Truncated)?;
+    let kind_bits = match version {
+        1 => 2,
+        2 | ENCODING_VERSION => KIND_BITS,
+        _ => return Err(DecodeError::UnsupportedVersion(version)),
+    };
+
+    let mut entries = Vec::new();
+    let mut position: Position = (0, 0);
+    while let Some((&header, rest)) = data.split_first() {
+        data = rest;
+        let dx = unzigzag(read_varint(&mut data)?);
+        let dy = unzigzag(read_varint(&mut data)?);
+        let kind = header & ((1 << kind_bits) - 1);
+        let size = if kind == OPEN_REGION {
+            Some((read_u16(&mut data)?, read_u16(&mut data)?))
+        } else {
+            None
+        };
+        let number = read_varint(&mut data)?;
+        let gesture = if version == ENCODING_VERSION {
+            let (&gesture, rest) = data.split_first().ok_or(DecodeError::Truncated)?;
+            data = rest;
+            gesture_of(gesture)?
+        } else {
+            None
+        };
+        for _ in 0..=header >> kind_bits {
+            position = (
+                offset(position.0, dx).ok_or(DecodeError::Overflow)?,
+                offset(position.1, dy).ok_or(DecodeError::Overflow)?,
+            );
+            entries.push(Entry {
+                action: action(kind, position, size)?,
+                number,
+                gesture,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn write_run(data: &mut Vec<u8>, (first, (dx, dy), length): (Entry, (i64, i64), usize)) {
+    data.push(kind(first.action) | ((length - 1) as u8) << KIND_BITS);
+    write_varint(data, zigzag(dx));
+    write_varint(data, zigzag(dy));
+    if let Some((width, height)) = size(first.action) {
+        write_varint(data, width as u64);
+        write_varint(data, height as u64);
+    }
+    write_varint(data, first.number);
+    data.push(match first.gesture {
+        None => 0,
+        Some(GestureKind::Tap) => 1,
+        Some(GestureKind::LongPress) => 2,
+        Some(GestureKind::DoubleTap) => 3,
+    });
+}
+
+fn kind(action: Action) -> u8 {
+    match action {
+        Action::Open(_) => 0,
+        Action::ToggleFlag(_) => 1,
+        Action::ForceOpen(_) => 2,
+        Action::Chord(_) => 3,
+        Action::OpenRegion(_) => OPEN_REGION,
+        Action::CycleMark(_) => 5,
+        Action::Probe(_) => 6,
+    }
+}
+
+fn size(action: Action) -> Option<(u16, u16)> {
+    match action {
+        Action::OpenRegion(rect) => Some((rect.width, rect.height)),
+        _ => None,
+    }
+}
+
+fn action(kind: u8, position: Position, size: Option<(u16, u16)>) -> Result<Action, DecodeError> {
+    match (kind, size) {
+        (0, _) => Ok(Action::Open(position)),
+        (1, _) => Ok(Action::ToggleFlag(position)),
+        (2, _) => Ok(Action::ForceOpen(position)),
+        (3, _) => Ok(Action::Chord(position)),
+        (OPEN_REGION, Some((width, height))) => Ok(Action::OpenRegion(Rect {
+            x: position.0,
+            y: position.1,
+            width,
+            height,
+        })),
+        (5, _) => Ok(Action::CycleMark(position)),
+        (6, _) => Ok(Action::Probe(position)),
+        _ => Err(DecodeError::UnknownKind(kind)),
+    }
+}
+
+fn gesture_of(byte: u8) -> Result<Option<GestureKind>, DecodeError> {
+    match byte {
+        0 => Ok(None),
+        1 => Ok(Some(GestureKind::Tap)),
+        2 => Ok(Some(GestureKind::LongPress)),
+        3 => Ok(Some(GestureKind::DoubleTap)),
+        _ => Err(DecodeError::UnknownGesture(byte)),
+    }
+}
+
+fn difference((x, y): Position, (last_x, last_y): Position) -> (i64, i64) {
+    (x as i64 - last_x as i64, y as i64 - last_y as i64)
+}
+
+fn offset(coordinate: u16, delta: i64) -> Option<u16> {
+    (coordinate as i64)
+        .checked_add(delta)
+        .and_then(|coordinate| u16::try_from(coordinate).ok())
+}
+
+// Map signed numbers to unsigned ones so that small magnitudes stay small:
+// 0, -1, 1, -2, 2, ... become 0, 1, 2, 3, 4, ...
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+// LEB128: seven bits per byte, lowest first, with the high bit set on every byte but
+// the last
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_u16(data: &mut &[u8]) -> Result<u16, DecodeError> {
+    u16::try_from(read_varint(data)?).map_err(|_| DecodeError::Overflow)
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data.split_first().ok_or(DecodeError::Truncated)?;
+        *data = rest;
+        value |= ((byte & 0x7f) as u64)
+            .checked_shl(shift)
+            .filter(|bits| bits >> shift == (byte & 0x7f) as u64)
+            .ok_or(DecodeError::Overflow)?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(action: Action, time_ms: u64) -> TimedAction {
+        TimedAction {
+            action,
+            time_ms,
+            gesture: None,
+        }
+    }
+
+    fn rect(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut data = Vec::new();
+            write_varint(&mut data, value);
+            assert_eq!(read_varint(&mut data.as_slice()), Ok(value), "{value}");
+        }
+        for value in [0, -1, 1, -65536, 65536, i64::MIN, i64::MAX] {
+            assert_eq!(unzigzag(zigzag(value)), value, "{value}");
+        }
+        assert_eq!(zigzag(-1), 1, "Small magnitudes stay small");
+        assert_eq!(
+            read_varint(&mut [0x80].as_slice()),
+            Err(DecodeError::Truncated)
+        );
+        assert_eq!(
+            read_varint(&mut [0xff; 11].as_slice()),
+            Err(DecodeError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_replay_round_trip() {
+        let replay = vec![
+            timed(Action::Open((15, 8)), 900),
+            timed(Action::ToggleFlag((14, 8)), 2100),
+            // A row flagged at a steady pace is a single run
+            timed(Action::ToggleFlag((15, 9)), 2400),
+            timed(Action::ToggleFlag((16, 9)), 2700),
+            timed(Action::ToggleFlag((17, 9)), 3000),
+            timed(Action::Chord((16, 8)), 3000),
+            timed(Action::ForceOpen((0, 0)), 60_000),
+            timed(Action::OpenRegion(rect(2, 3, 4, 5)), 61_000),
+            timed(Action::OpenRegion(rect(2, 3, 4, 6)), 62_000),
+            timed(Action::CycleMark((2, 3)), 62_500),
+            timed(Action::Probe((2, 4)), 63_000),
+            timed(Action::Open((u16::MAX, u16::MAX)), u64::MAX),
+        ];
+        let data = encode_replay(&replay);
+        assert_eq!(decode_replay(&data), Ok(replay.clone()), "Exact round trip");
+
+        // ================================================
+        // Test that the gestures are kept, and that they split runs
+        let gestures = [
+            None,
+            Some(GestureKind::Tap),
+            Some(GestureKind::LongPress),
+            Some(GestureKind::DoubleTap),
+        ];
+        let replay: Vec<TimedAction> = (0..8u16)
+            .map(|index| TimedAction {
+                gesture: gestures[index as usize / 2],
+                ..timed(Action::ToggleFlag((index + 1, 0)), (index as u64 + 1) * 300)
+            })
+            .collect();
+        let data = encode_replay(&replay);
+        assert_eq!(decode_replay(&data), Ok(replay), "Gestures round trip");
+        assert_eq!(data.len(), 1 + 4 * 6, "4 runs");
+        assert_eq!(
+            decode_replay(&encode_replay(&[])),
+            Ok(vec![]),
+            "Empty replay"
+        );
+
+        // ================================================
+        // Test the size of a long replay of clicks near each other
+        let replay: Vec<TimedAction> = (0..400u16)
+            .map(|index| {
+                let position = (index % 30, index / 30 % 16);
+                let action = if index % 5 == 0 {
+                    Action::ToggleFlag(position)
+                } else {
+                    Action::Open(position)
+                };
+                timed(action, index as u64 * 1500 + (index as u64 * 37) % 400)
+            })
+            .collect();
+        let data = encode_replay(&replay);
+        assert_eq!(decode_replay(&data), Ok(replay.clone()));
+        let json = serde_json::to_string(&replay).unwrap();
+        assert!(
+            data.len() * 10 <= json.len(),
+            "{} bytes instead of {}",
+            data.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode_replay(&[]), Err(DecodeError::Truncated));
+        assert_eq!(decode_replay(&[4]), Err(DecodeError::UnsupportedVersion(4)));
+        let data = encode_replay(&[timed(Action::Open((3, 4)), 10)]);
+        assert_eq!(
+            decode_replay(&data[..data.len() - 1]),
+            Err(DecodeError::Truncated)
+        );
+        // One step left of the first column and a step too large for any board
+        assert_eq!(
+            decode_replay(&[ENCODING_VERSION, 0, 1, 0, 0, 0]),
+            Err(DecodeError::Overflow)
+        );
+        let mut data = vec![ENCODING_VERSION, 0];
+        write_varint(&mut data, zigzag(i64::MAX));
+        data.extend([0, 0, 0]);
+        assert_eq!(decode_replay(&data), Err(DecodeError::Overflow));
+        assert_eq!(
+            decode_replay(&[ENCODING_VERSION, 7, 0, 0, 0, 0]),
+            Err(DecodeError::UnknownKind(7))
+        );
+        assert_eq!(
+            decode_replay(&[ENCODING_VERSION, 0, 0, 0, 0, 4]),
+            Err(DecodeError::UnknownGesture(4))
+        );
+    }
+
+    #[test]
+    fn test_decode_old_versions() {
+        // A run of two flags one cell apart, 300 ms apart, and a chord
+        let replay = vec![
+            timed(Action::ToggleFlag((1, 0)), 300),
+            timed(Action::ToggleFlag((2, 0)), 600),
+            timed(Action::Chord((1, 1)), 600),
+        ];
+        let data = [1, 0b101, 2, 0, 0xac, 0x02, 3, 1, 2, 0];
+        assert_eq!(decode_replay(&data), Ok(replay.clone()), "Version 1");
+        let data = [2, 0b1001, 2, 0, 0xac, 0x02, 3, 1, 2, 0];
+        assert_eq!(decode_replay(&data), Ok(replay), "Version 2");
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let mut minesweeper = Minesweeper::new_with_seed(16, 16, 40, 11);
+        let start = minesweeper.clone();
+        minesweeper.open((8, 8));
+        // Flag the proven mines and open the proven safe cells
+        for _ in 0..40 {
+            let partition = minesweeper.partition();
+            let hidden = |position: &&Position| {
+                minesweeper.cell_state(**position) == crate::model::CellState::Hidden
+            };
+            let components = partition.components.iter();
+            let mine = components.clone().flat_map(|c| &c.mines).find(hidden);
+            let safe = components.flat_map(|c| &c.safe).find(hidden);
+            match (mine, safe) {
+                (Some(&mine), _) => minesweeper.apply(Action::ToggleFlag(mine)),
+                (_, Some(&safe)) => minesweeper.apply(Action::Open(safe)),
+                _ => break,
+            };
+        }
+        assert!(minesweeper.history().len() > 10, "Long enough");
+        let data = minesweeper.encode_history();
+        let mut replayed = start.clone();
+        assert_eq!(replayed.replay_history(&data), Ok(()));
+        assert_eq!(replayed.history(), minesweeper.history(), "Same moves");
+        assert_eq!(replayed.to_string(), minesweeper.to_string(), "Same board");
+
+        // ================================================
+        // Test that another game is detected
+        let mut other = Minesweeper::new_with_seed(16, 16, 40, 12);
+        assert!(matches!(
+            other.replay_history(&data),
+            Err(DecodeError::Mismatch { .. })
+        ));
+    }
+}
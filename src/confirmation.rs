@@ -0,0 +1,266 @@
+// Two-step confirmation of risky actions. Frontends pass actions to
+// `Minesweeper::attempt`, which plays a safe action right away but answers a risky one
+// with the reason and a token. Only `Minesweeper::confirm` with that token plays it. The
+// engine decides what is risky, so every frontend asks about the same actions, and the
+// history keeps the reason of every confirmed move.
+use crate::{
+    action::Action,
+    diff::CellChange,
+    model::{CellState, GameStatus, Minesweeper, Position},
+};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationPolicy {
+    // Play every action right away
+    #[default]
+    Off,
+    // Confirm opening a cell which has a mine in more than half of the layouts of the
+    // board
+    Risky,
+    // Also confirm chords around a flag which the numbers prove wrong
+    Strict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ConfirmationReason {
+    // The cell has a mine in more than half of the layouts consistent with the numbers
+    // and the mine total
+    LikelyMine { position: Position },
+    // The flag is on a cell the numbers prove safe, so the chord opens a mine
+    WrongFlag { position: Position },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Attempt {
+    // The action was played, the changes are empty if it didn't make a move
+    Played {
+        changes: Vec<CellChange>,
+    },
+    // Nothing was played, pass the token to `Minesweeper::confirm` to play the action
+    NeedsConfirmation {
+        reason: ConfirmationReason,
+        token: u32,
+    },
+    // Nothing was played, the debouncer ignored the action as a repeat, see `Debouncer`
+    Debounced,
+}
+
+// The action of the last confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PendingConfirmation {
+    action: Action,
+    reason: ConfirmationReason,
+    token: u32,
+}
+
+impl Minesweeper {
+    pub fn set_confirmation_policy(&mut self, policy: ConfirmationPolicy) {
+        self.confirmation_policy = policy;
+    }
+
+    pub fn confirmation_policy(&self) -> ConfirmationPolicy {
+        self.confirmation_policy
+    }
+
+    // Play the action unless the policy asks for a confirmation. A new prompt replaces
+    // the previous one
+    pub fn attempt(&mut self, action: Action) -> Attempt {
+        let Some(reason) = self.confirmation_reason(action) else {
+            return Attempt::Played {
+                changes: self.apply_with_changes(action),
+            };
+        };
+
+        self.tokens_issued = self.tokens_issued.wrapping_add(1);
+        let token = self.tokens_issued;
+        self.pending_confirmation = Some(PendingConfirmation {
+            action,
+            reason,
+            token,
+        });
+        Attempt::NeedsConfirmation { reason, token }
+    }
+
+    // Play the action of the last prompt and return the cells it changed. Return None if
+    // the token isn't the one of the last prompt, or if a move was made, undone or redone
+    // since, because the prompt was about another board
+    pub fn confirm(&mut self, token: u32) -> Option<Vec<CellChange>> {
+        let pending = self
+            .pending_confirmation
+            .filter(|pending| pending.token == token)?;
+        self.pending_confirmation = None;
+
+        let moves = self.moves();
+        let changes = self.apply_with_changes(pending.action);
+        if self.moves() != moves {
+            self.history.mark_confirmed(pending.reason);
+        }
+        Some(changes)
+    }
+
+    // Why the action needs a confirmation under the current policy, if it does
+    pub fn confirmation_reason(&self, action: Action) -> Option<ConfirmationReason> {
+        if self.confirmation_policy == ConfirmationPolicy::Off
+            || self.status() != GameStatus::InProgress
+        {
+            return None;
+        }
+
+        match action {
+            Action::Open(position)
+                if matches!(
+                    self.cell_state(position),
+                    CellState::Hidden | CellState::Questioned
+                ) =>
+            {
+                self.likely_mine(position)
+            }
+            Action::ForceOpen(position)
+                if matches!(
+                    self.cell_state(position),
+                    CellState::Hidden | CellState::Flagged | CellState::Questioned
+                ) =>
+            {
+                self.likely_mine(position)
+            }
+            Action::Chord(position)
+                if self.confirmation_policy == ConfirmationPolicy::Strict
+                    && !self.chord_targets(position).is_empty() =>
+            {
+                let flags: Vec<Position> = self
+                    .neighbours(position)
+                    .filter(|neighbour| self.flagged_positions.contains(neighbour))
+                    .collect();
+                self.provably_safe_cells(&flags)
+                    .first()
+                    .map(|&position| ConfirmationReason::WrongFlag { position })
+            }
+            _ => None,
+        }
+    }
+
+    fn likely_mine(&self, position: Position) -> Option<ConfirmationReason> {
+        self.mine_odds(position)
+            .filter(|&odds| odds > 0.5)
+            .map(|_| ConfirmationReason::LikelyMine { position })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attempt_open() {
+        //     * 1 .
+        //     1 1 .
+        //     . . .
+        let mut minesweeper = crate::board!["*o.", "oo.", "..."];
+        let likely_mine = ConfirmationReason::LikelyMine { position: (0, 0) };
+
+        // ================================================
+        // Test that nothing is confirmed without a policy
+        assert_eq!(minesweeper.confirmation_reason(Action::Open((0, 0))), None);
+
+        // ================================================
+        // Test that a likely mine waits for the confirmation
+        minesweeper.set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(
+            minesweeper.attempt(Action::Open((0, 0))),
+            Attempt::NeedsConfirmation {
+                reason: likely_mine,
+                token: 1
+            }
+        );
+        assert_eq!(minesweeper.moves(), 0, "Nothing played");
+        assert_eq!(
+            minesweeper.confirmation_reason(Action::Open((2, 0))),
+            None,
+            "Safe cell"
+        );
+
+        // A mine in the middle leaves 2 ways to place the other one in the sea, two mines
+        // at the ends leave 1
+        let mut weighted = crate::board!["*o.o*.."];
+        weighted.set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(
+            weighted.confirmation_reason(Action::Open((2, 0))),
+            Some(ConfirmationReason::LikelyMine { position: (2, 0) }),
+            "Weighted by the sea"
+        );
+        assert_eq!(
+            weighted.confirmation_reason(Action::Open((0, 0))),
+            None,
+            "Mine in a third of the layouts"
+        );
+        let mut sea = crate::board!["**", "*."];
+        sea.set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(
+            sea.confirmation_reason(Action::Open((1, 1))),
+            Some(ConfirmationReason::LikelyMine { position: (1, 1) }),
+            "Sea cell"
+        );
+        let mut half = crate::board!["*.", ".*"];
+        half.set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(
+            half.confirmation_reason(Action::Open((1, 0))),
+            None,
+            "Mine in half of the layouts"
+        );
+        assert_eq!(minesweeper.confirm(2), None, "Wrong token");
+        assert!(minesweeper.confirm(1).is_some());
+        assert_eq!(minesweeper.status(), GameStatus::Lost);
+        assert_eq!(minesweeper.confirm(1), None, "Token used");
+        assert_eq!(
+            minesweeper.history()[0].confirmed(),
+            Some(likely_mine),
+            "Confirmation recorded"
+        );
+
+        // ================================================
+        // Test that a move in between invalidates the token
+        let mut minesweeper = crate::board!["*o.", "oo.", "..."];
+        minesweeper.set_confirmation_policy(ConfirmationPolicy::Risky);
+        let Attempt::NeedsConfirmation { token, .. } = minesweeper.attempt(Action::Open((0, 0)))
+        else {
+            panic!("Confirmation expected");
+        };
+        let Attempt::Played { changes } = minesweeper.attempt(Action::ToggleFlag((2, 2))) else {
+            panic!("Played expected");
+        };
+        assert_eq!(changes.len(), 1, "Flag placed");
+        assert_eq!(minesweeper.confirm(token), None, "Board changed");
+        assert_eq!(minesweeper.history()[0].confirmed(), None);
+    }
+
+    #[test]
+    fn test_attempt_chord() {
+        //     * 1 F
+        //     1 1 .
+        //     . . .
+        let mut minesweeper = crate::board!["*of", "oo.", "..."];
+        let chord = Action::Chord((1, 0));
+
+        // ================================================
+        // Test that a chord around a wrong flag is only confirmed in strict mode
+        minesweeper.set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(minesweeper.confirmation_reason(chord), None);
+        minesweeper.set_confirmation_policy(ConfirmationPolicy::Strict);
+        assert_eq!(
+            minesweeper.confirmation_reason(chord),
+            Some(ConfirmationReason::WrongFlag { position: (2, 0) })
+        );
+        assert_eq!(
+            minesweeper.confirmation_reason(Action::Chord((0, 1))),
+            None,
+            "Flags don't match the number"
+        );
+    }
+}
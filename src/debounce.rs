@@ -0,0 +1,181 @@
+// Filters out repeated input caused by touchscreen jitter and bouncing mouse buttons.
+// An action identical to the last accepted one is ignored if it comes within the
+// debounce window. The window is measured from the accepted action, so holding a
+// bouncing button doesn't suppress the action forever.
+//
+// A double click on an open number isn't jitter: the first click of it does nothing, so
+// the second one is played as a chord, which is what the player meant.
+use crate::{
+    action::Action,
+    clock::Clock,
+    model::{CellState, Minesweeper},
+};
+
+#[derive(Debug, Clone)]
+pub struct Debouncer<C> {
+    clock: C,
+    window_ms: u64,
+    // The last accepted action, the time it was accepted and whether it opened an open
+    // number, which a second click turns into a chord
+    last: Option<(Action, u64, bool)>,
+    ignored: u32,
+}
+
+impl<C: Clock> Debouncer<C> {
+    // A window of zero accepts every action
+    pub fn new(clock: C, window_ms: u64) -> Self {
+        Self {
+            clock,
+            window_ms,
+            last: None,
+            ignored: 0,
+        }
+    }
+
+    pub fn window_ms(&self) -> u64 {
+        self.window_ms
+    }
+
+    pub fn set_window_ms(&mut self, window_ms: u64) {
+        self.window_ms = window_ms;
+    }
+
+    // Forget the last action, e.g. when a new game starts
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    // Number of actions ignored as duplicates
+    pub fn ignored(&self) -> u32 {
+        self.ignored
+    }
+
+    // Return the action to play, which is a chord for a double click on an open number,
+    // or None if the action is a duplicate and should be ignored
+    pub fn accept(&mut self, action: Action, minesweeper: &Minesweeper) -> Option<Action> {
+        let now = self.clock.now_ms();
+        match self.last {
+            Some((last, accepted_at, on_number))
+                if last == action && now.saturating_sub(accepted_at) < self.window_ms =>
+            {
+                if let (Action::Open(position), true) = (action, on_number) {
+                    let chord = Action::Chord(position);
+                    self.last = Some((chord, now, false));
+                    return Some(chord);
+                }
+                self.ignored += 1;
+                None
+            }
+            _ => {
+                let on_number = matches!(
+                    action,
+                    Action::Open(position)
+                        if matches!(minesweeper.cell_state(position), CellState::Open(1..))
+                );
+                self.last = Some((action, now, on_number));
+                Some(action)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_accept() {
+        let clock = MockClock::new(0);
+        let minesweeper = crate::board!["*..", "...", "..."];
+        let mut debouncer = Debouncer::new(&clock, 100);
+        assert!(debouncer
+            .accept(Action::Open((1, 1)), &minesweeper)
+            .is_some());
+
+        // ================================================
+        // Test that duplicates within the window are ignored
+        clock.advance(50);
+        assert!(
+            debouncer
+                .accept(Action::Open((1, 1)), &minesweeper)
+                .is_none(),
+            "Duplicate open"
+        );
+        assert!(
+            debouncer
+                .accept(Action::Open((2, 1)), &minesweeper)
+                .is_some(),
+            "Another cell"
+        );
+        assert!(
+            debouncer
+                .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+                .is_some(),
+            "Another action"
+        );
+        assert!(
+            debouncer
+                .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+                .is_none(),
+            "Duplicate flag"
+        );
+        assert_eq!(debouncer.ignored(), 2, "2 actions ignored");
+        debouncer.reset();
+        assert!(
+            debouncer
+                .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+                .is_some(),
+            "Last action forgotten"
+        );
+
+        // ================================================
+        // Test that the window is measured from the accepted action
+        clock.advance(60);
+        assert!(debouncer
+            .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+            .is_none());
+        clock.advance(40);
+        assert!(
+            debouncer
+                .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+                .is_some(),
+            "Window passed"
+        );
+
+        // ================================================
+        // Test that a zero window accepts everything
+        debouncer.set_window_ms(0);
+        assert!(debouncer
+            .accept(Action::ToggleFlag((2, 1)), &minesweeper)
+            .is_some());
+        assert_eq!(debouncer.ignored(), 3);
+    }
+
+    #[test]
+    fn test_double_click_chord() {
+        let clock = MockClock::new(0);
+        let mut minesweeper = crate::board!["*o.", "...", "..."];
+        minesweeper.toggle_flag((0, 0));
+        let mut debouncer = Debouncer::new(&clock, 100);
+
+        // ================================================
+        // Test that a double click on an open number is played as a chord
+        let open = Action::Open((1, 0));
+        assert_eq!(debouncer.accept(open, &minesweeper), Some(open));
+        clock.advance(50);
+        assert_eq!(
+            debouncer.accept(open, &minesweeper),
+            Some(Action::Chord((1, 0))),
+            "Coalesced into a chord"
+        );
+        assert_eq!(debouncer.ignored(), 0, "Nothing ignored");
+
+        // ================================================
+        // Test that a double click on a hidden cell isn't, although it opens a number
+        let open = Action::Open((1, 1));
+        debouncer.accept(open, &minesweeper);
+        minesweeper.open((1, 1));
+        assert_eq!(debouncer.accept(open, &minesweeper), None, "Ignored");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    action::Action,
+    game_state::CellView,
+    model::{CellState, Minesweeper, Position},
+    snapshot::GameSnapshot,
+};
+use serde::{Serialize, Serializer};
+
+// A cell whose visible state differs between two boards. In JSON the states are cell
+// views, e.g. `{"position":[1,0],"before":{"state":"hidden"},"after":{"state":"open",
+// "mines_around":1}}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CellChange {
+    pub position: Position,
+    #[serde(serialize_with = "as_cell_view")]
+    pub before: CellState,
+    #[serde(serialize_with = "as_cell_view")]
+    pub after: CellState,
+}
+
+fn as_cell_view<S: Serializer>(cell_state: &CellState, serializer: S) -> Result<S::Ok, S::Error> {
+    CellView::from(*cell_state).serialize(serializer)
+}
+
+impl Minesweeper {
+    // Apply an action and return the cells it changed in row-major order, so frontends
+    // update only those cells instead of redrawing the board. Besides the opened cells,
+    // the end of a game reveals the board and blind mode hides aging numbers
+    pub fn apply_with_changes(&mut self, action: Action) -> Vec<CellChange> {
+        let before = self.snapshot();
+        self.apply(action);
+        diff_snapshots(&before, &self.snapshot()).unwrap_or_default()
+    }
+}
+
+// Compare what the player sees on two boards. Return the differing cells in row-major
+// order, or None if the boards have different sizes
+pub fn diff_boards(before: &Minesweeper, after: &Minesweeper) -> Option<Vec<CellChange>> {
+    diff_snapshots(&before.snapshot(), &after.snapshot())
+}
+
+pub fn diff_snapshots(before: &GameSnapshot, after: &GameSnapshot) -> Option<Vec<CellChange>> {
+    if (before.width(), before.height()) != (after.width(), after.height()) {
+        return None;
+    }
+    if before.ptr_eq(after) {
+        return Some(Vec::new());
+    }
+
+    let width = before.width() as usize;
+    let changes = before
+        .cells()
+        .iter()
+        .zip(after.cells())
+        .enumerate()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(index, (&before, &after))| CellChange {
+            position: ((index % width) as u16, (index / width) as u16),
+            before,
+            after,
+        })
+        .collect();
+    Some(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_boards() {
+        let before = crate::board!["*...", "....", "...*"];
+        assert_eq!(diff_boards(&before, &before), Some(vec![]), "Same board");
+
+        // ================================================
+        // Test the changes made by actions
+        let mut after = before.sandbox();
+        after.toggle_flag((0, 0));
+        after.open((1, 0));
+        assert_eq!(
+            diff_boards(&before, &after),
+            Some(vec![
+                CellChange {
+                    position: (0, 0),
+                    before: CellState::Hidden,
+                    after: CellState::Flagged,
+                },
+                CellChange {
+                    position: (1, 0),
+                    before: CellState::Hidden,
+                    after: CellState::Open(1),
+                },
+            ])
+        );
+
+        // ================================================
+        // Test that only visible state is compared
+        let mut moved_mine = before.sandbox();
+        moved_mine.mines.remove(&(3, 2));
+        moved_mine.mines.insert((3, 1));
+        assert_eq!(
+            diff_boards(&before, &moved_mine),
+            Some(vec![]),
+            "Mines are hidden"
+        );
+
+        // ================================================
+        // Test boards of different sizes
+        let other = crate::board!["*..."];
+        assert_eq!(diff_boards(&before, &other), None);
+    }
+
+    #[test]
+    fn test_apply_with_changes() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        let changes = minesweeper.apply_with_changes(Action::Open((1, 0)));
+        assert_eq!(
+            changes,
+            vec![CellChange {
+                position: (1, 0),
+                before: CellState::Hidden,
+                after: CellState::Open(1),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_string(&changes).unwrap(),
+            r#"[{"position":[1,0],"before":{"state":"hidden"},"after":{"state":"open","mines_around":1}}]"#
+        );
+        assert_eq!(
+            minesweeper.apply_with_changes(Action::Open((1, 0))),
+            vec![],
+            "Already open"
+        );
+
+        // ================================================
+        // Test that a loss reveals the rest of the board
+        let changes = minesweeper.apply_with_changes(Action::Open((0, 0)));
+        assert_eq!(changes.len(), 8, "Every other cell");
+        assert_eq!(changes[0].after, CellState::Exploded);
+        assert_eq!(changes[7].after, CellState::Mine);
+    }
+}
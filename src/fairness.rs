@@ -0,0 +1,147 @@
+// Commit-reveal scheme proving that the board layout was fixed before the game started.
+//
+// Before the first move the engine publishes a commitment: the SHA-256 hash of the
+// canonical layout followed by a secret random nonce. When the game is over the layout
+// and the nonce are revealed, and anyone can recompute the hash with `verify_commitment`.
+// The nonce keeps the layout from being brute-forced out of the hash during the game.
+use crate::model::{GameStatus, Minesweeper, Position};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardReveal {
+    pub width: u16,
+    pub height: u16,
+    // Mine positions sorted by x, then y
+    pub mines: Vec<Position>,
+    // Hex-encoded nonce
+    pub nonce: String,
+}
+
+impl BoardReveal {
+    // Canonical text of the layout, e.g. `10x10:0,3;4,1`
+    pub fn layout(&self) -> String {
+        let mines: Vec<String> = self.mines.iter().map(|(x, y)| format!("{x},{y}")).collect();
+        format!("{}x{}:{}", self.width, self.height, mines.join(";"))
+    }
+
+    pub fn commitment(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.layout().as_bytes());
+        hasher.update(b"|");
+        hasher.update(self.nonce.as_bytes());
+        to_hex(&hasher.finalize())
+    }
+}
+
+pub fn verify_commitment(commitment: &str, reveal: &BoardReveal) -> bool {
+    // The layout must be canonical, otherwise the same board could have many hashes
+    reveal.mines.windows(2).all(|pair| pair[0] < pair[1])
+        && reveal.commitment().eq_ignore_ascii_case(commitment)
+}
+
+impl Minesweeper {
+    // Commit to the current layout and return the commitment. The nonce is generated
+    // once per game, so calling it again returns the same commitment. Undoing the first
+    // click would move mines back and change the committed layout, so moves before the
+    // commitment can't be taken back
+    pub fn commit(&mut self) -> String {
+        if self.commitment_nonce.is_none() {
+            self.clear_history();
+        }
+        let nonce = self
+            .commitment_nonce
+            .get_or_insert_with(|| to_hex(&rand::thread_rng().gen::<[u8; 32]>()))
+            .clone();
+        self.board_reveal(nonce).commitment()
+    }
+
+    // Reveal the layout and the nonce. Only possible after a commitment and once the
+    // game is over
+    pub fn reveal(&self) -> Option<BoardReveal> {
+        if self.status() == GameStatus::InProgress {
+            return None;
+        }
+        let nonce = self.commitment_nonce.clone()?;
+        Some(self.board_reveal(nonce))
+    }
+
+    fn board_reveal(&self, nonce: String) -> BoardReveal {
+        let mut mines: Vec<Position> = self.mines.iter().collect();
+        mines.sort();
+        BoardReveal {
+            width: self.width(),
+            height: self.height(),
+            mines,
+            nonce,
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let reveal = BoardReveal {
+            width: 10,
+            height: 8,
+            mines: vec![(0, 3), (4, 1)],
+            nonce: "00".to_string(),
+        };
+        assert_eq!(reveal.layout(), "10x8:0,3;4,1");
+    }
+
+    #[test]
+    fn test_commit_reveal() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+
+        // ================================================
+        // Test that nothing can be revealed without a commitment
+        let mine = minesweeper.mines.iter().next().unwrap();
+        let mut lost_game = minesweeper.sandbox();
+        lost_game.set_safe_first_click(false);
+        lost_game.open(mine);
+        assert_eq!(lost_game.reveal(), None, "No commitment");
+
+        // ================================================
+        // Test that nothing is revealed while the game is in progress
+        let commitment = minesweeper.commit();
+        assert_eq!(commitment.len(), 64, "SHA-256 in hex");
+        assert_eq!(minesweeper.commit(), commitment, "Same commitment");
+        assert_eq!(minesweeper.reveal(), None, "Game in progress");
+
+        // ================================================
+        // Test that the revealed layout matches the commitment
+        minesweeper.open(mine);
+        let reveal = minesweeper.reveal().unwrap();
+        assert_eq!(reveal.mines.len(), 10, "All mines revealed");
+        assert!(reveal.mines.contains(&mine));
+        assert!(verify_commitment(&commitment, &reveal), "Valid commitment");
+        assert!(
+            verify_commitment(&commitment.to_uppercase(), &reveal),
+            "Hex case doesn't matter"
+        );
+
+        // ================================================
+        // Test that a tampered layout or nonce fails the verification
+        let mut tampered = reveal.clone();
+        tampered.mines[0].0 = (tampered.mines[0].0 + 1) % 10;
+        tampered.mines.sort();
+        assert!(!verify_commitment(&commitment, &tampered), "Moved mine");
+
+        let mut tampered = reveal.clone();
+        tampered.nonce.replace_range(0..1, "x");
+        assert!(!verify_commitment(&commitment, &tampered), "Changed nonce");
+
+        let mut tampered = reveal;
+        tampered.mines.reverse();
+        assert!(!verify_commitment(&commitment, &tampered), "Unsorted mines");
+    }
+}
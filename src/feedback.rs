@@ -0,0 +1,146 @@
+use crate::model::{Minesweeper, Position};
+use serde::Serialize;
+
+// Cascades opening at least this many cells are huge
+pub const HUGE_CASCADE_CELLS: usize = 20;
+// Opening a cell with at least this many mines around it is a near miss
+pub const NEAR_MISS_MINES: u8 = 5;
+
+// Semantic feedback events for sounds and vibration. Unlike the board state,
+// they describe how an action felt rather than what changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type")]
+pub enum FeedbackEvent {
+    SmallCascade { cells: usize },
+    HugeCascade { cells: usize },
+    NearMiss { x: u16, y: u16, mines_around: u8 },
+    Victory,
+    Explosion { x: u16, y: u16 },
+}
+
+impl FeedbackEvent {
+    // Derive the events caused by opening a position which opened `opened_cells` cells
+    pub(crate) fn after_open(
+        minesweeper: &Minesweeper,
+        (x, y): Position,
+        opened_cells: usize,
+    ) -> Vec<FeedbackEvent> {
+        let mut events = Vec::new();
+
+        if minesweeper.game_over {
+            events.push(FeedbackEvent::Explosion { x, y });
+            return events;
+        }
+
+        match opened_cells {
+            0 | 1 => {}
+            cells if cells < HUGE_CASCADE_CELLS => {
+                events.push(FeedbackEvent::SmallCascade { cells });
+            }
+            cells => events.push(FeedbackEvent::HugeCascade { cells }),
+        }
+
+        let mines_around = minesweeper.mines_around((x, y));
+        if mines_around >= NEAR_MISS_MINES {
+            events.push(FeedbackEvent::NearMiss { x, y, mines_around });
+        }
+
+        if minesweeper.all_safe_cells_open() {
+            events.push(FeedbackEvent::Victory);
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feedback_events() {
+        // ================================================
+        // Test the case when a single cell is opened
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        minesweeper.mines.insert((5, 6));
+        minesweeper.mines.insert((0, 0));
+        minesweeper.open((5, 5));
+        assert!(
+            minesweeper.take_feedback_events().is_empty(),
+            "No feedback for a single cell"
+        );
+
+        // ================================================
+        // Test the case when a huge cascade wins the game
+        minesweeper.open((9, 0));
+        assert_eq!(
+            minesweeper.take_feedback_events(),
+            vec![
+                FeedbackEvent::HugeCascade { cells: 97 },
+                FeedbackEvent::Victory
+            ],
+            "Huge cascade and victory"
+        );
+        assert!(
+            minesweeper.take_feedback_events().is_empty(),
+            "Events are drained"
+        );
+
+        // ================================================
+        // Test the case when a small cascade is opened
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Surround the 3x3 area in the upper left corner with mines
+        (0..4).for_each(|i| {
+            minesweeper.mines.insert((i, 3));
+            minesweeper.mines.insert((3, i));
+        });
+        minesweeper.open((0, 0));
+        assert_eq!(
+            minesweeper.take_feedback_events(),
+            vec![FeedbackEvent::SmallCascade { cells: 9 }],
+            "Small cascade"
+        );
+
+        // ================================================
+        // Test the case when an opened cell has 5 mines around it
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        [(4, 4), (5, 4), (6, 4), (4, 5), (6, 5)]
+            .into_iter()
+            .for_each(|mine| {
+                minesweeper.mines.insert(mine);
+            });
+        minesweeper.open((5, 5));
+        assert_eq!(
+            minesweeper.take_feedback_events(),
+            vec![FeedbackEvent::NearMiss {
+                x: 5,
+                y: 5,
+                mines_around: 5
+            }],
+            "Near miss"
+        );
+
+        // ================================================
+        // Test the case when a mine is opened
+        minesweeper.open((4, 4));
+        assert_eq!(
+            minesweeper.take_feedback_events(),
+            vec![FeedbackEvent::Explosion { x: 4, y: 4 }],
+            "Explosion"
+        );
+
+        // ================================================
+        // Test the case when nothing is opened
+        minesweeper.open((4, 4));
+        assert!(
+            minesweeper.take_feedback_events().is_empty(),
+            "No feedback when nothing happens"
+        );
+    }
+}
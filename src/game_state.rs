@@ -0,0 +1,137 @@
+use crate::{
+    annotation::Annotation,
+    model::{CellState, GameStatus, Minesweeper},
+    region::RegionStatus,
+};
+use serde::Serialize;
+
+// Structured state of the whole board, for frontends which render their own UI instead
+// of the text of `Display`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GameState {
+    pub width: u16,
+    pub height: u16,
+    pub status: GameStatus,
+    pub game_over: bool,
+    // Mines minus flags, negative when there are more flags than mines
+    pub remaining_mines: i32,
+    pub rows: Vec<Vec<CellView>>,
+    pub annotations: Vec<Annotation>,
+    // Empty unless the board is divided into regions, see `Minesweeper::set_regions`
+    pub regions: Vec<RegionStatus>,
+}
+
+// A cell as the player sees it, e.g. `{"state":"open","mines_around":2}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CellView {
+    Hidden,
+    Flagged,
+    Questioned,
+    // The number is null while blind mode hides it
+    Open { mines_around: Option<u8> },
+    Mine,
+    Exploded,
+}
+
+impl From<CellState> for CellView {
+    fn from(cell_state: CellState) -> Self {
+        match cell_state {
+            CellState::Hidden => CellView::Hidden,
+            CellState::Flagged => CellView::Flagged,
+            CellState::Questioned => CellView::Questioned,
+            CellState::Open(mines_around) => CellView::Open {
+                mines_around: Some(mines_around),
+            },
+            CellState::Masked => CellView::Open { mines_around: None },
+            CellState::Mine => CellView::Mine,
+            CellState::Exploded => CellView::Exploded,
+        }
+    }
+}
+
+impl GameState {
+    pub fn new(minesweeper: &Minesweeper) -> Self {
+        Self {
+            width: minesweeper.width(),
+            height: minesweeper.height(),
+            status: minesweeper.status(),
+            game_over: minesweeper.game_over,
+            remaining_mines: minesweeper.remaining_mines(),
+            rows: (0..minesweeper.height())
+                .map(|y| {
+                    (0..minesweeper.width())
+                        .map(|x| minesweeper.cell_state((x, y)).into())
+                        .collect()
+                })
+                .collect(),
+            annotations: minesweeper.annotations(),
+            regions: minesweeper.region_statuses(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let mut minesweeper = crate::board!["*.", ".."];
+        minesweeper.toggle_flag((1, 1));
+        minesweeper.open((1, 0));
+        let state = GameState::new(&minesweeper);
+        assert_eq!(
+            state.rows,
+            vec![
+                vec![
+                    CellView::Hidden,
+                    CellView::Open {
+                        mines_around: Some(1)
+                    }
+                ],
+                vec![CellView::Hidden, CellView::Flagged],
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&state).unwrap(),
+            r#"{"width":2,"height":2,"status":"in_progress","game_over":false,"remaining_mines":0,"rows":[[{"state":"hidden"},{"state":"open","mines_around":1}],[{"state":"hidden"},{"state":"flagged"}]],"annotations":[],"regions":[]}"#
+        );
+
+        // ================================================
+        // Test a lost game
+        minesweeper.open((0, 0));
+        let state = GameState::new(&minesweeper);
+        assert!(state.game_over);
+        assert_eq!(state.status, GameStatus::Lost);
+        assert_eq!(state.rows[0][0], CellView::Exploded);
+    }
+
+    #[test]
+    fn test_regions() {
+        let mut minesweeper = crate::board!["*..", "..."];
+        let region = |name: &str, x| crate::region::Region {
+            name: name.to_string(),
+            x,
+            y: 0,
+            width: 1,
+            height: 2,
+        };
+        minesweeper
+            .set_regions(vec![region("west", 2), region("east", 1)])
+            .unwrap();
+        minesweeper.open((2, 0));
+        assert_eq!(
+            serde_json::to_string(&GameState::new(&minesweeper).regions).unwrap(),
+            r#"[{"name":"west","state":"cleared"},{"name":"east","state":"active"}]"#
+        );
+    }
+
+    #[test]
+    fn test_masked_cell() {
+        assert_eq!(
+            serde_json::to_string(&CellView::from(CellState::Masked)).unwrap(),
+            r#"{"state":"open","mines_around":null}"#
+        );
+    }
+}
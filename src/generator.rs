@@ -0,0 +1,313 @@
+use crate::{hash::HashSet, model::Position};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+// Places the mines of a new board. Must return exactly `mines_count` distinct positions
+// on the board
+pub trait MineGenerator {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position>;
+}
+
+// Uniformly random layouts, the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomGenerator;
+
+impl MineGenerator for RandomGenerator {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+        random_layout(&mut rand::thread_rng(), width, height, mines_count)
+    }
+}
+
+// Uniformly random layouts drawn from a seeded RNG, so a seed always gives the same board
+// with the same version of the crate
+#[derive(Debug, Clone)]
+pub struct SeededGenerator {
+    rng: StdRng,
+}
+
+impl SeededGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl MineGenerator for SeededGenerator {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+        random_layout(&mut self.rng, width, height, mines_count)
+    }
+}
+
+// Draw distinct positions until there are enough. Sorted, so the layout only depends on
+// the RNG and not on the order of a hash set
+fn random_layout(rng: &mut impl Rng, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+    let mut positions = HashSet::with_capacity_and_hasher(mines_count as usize, Default::default());
+    while positions.len() < mines_count as usize {
+        positions.insert((rng.gen_range(0..width), rng.gen_range(0..height)));
+    }
+    let mut positions: Vec<Position> = positions.into_iter().collect();
+    positions.sort();
+    positions
+}
+
+// The generator of the classic Windows Minesweeper. It draws the column and then the row
+// of every mine from the C runtime `rand()` of Microsoft Visual C++, skipping cells which
+// already hold a mine, so a seed passed to `srand()` gives the same board as the original.
+// The original moves a mine under the first click to the top-left free cell, which is
+// not part of the generation
+#[derive(Debug, Clone, Copy)]
+pub struct WindowsGenerator {
+    state: u32,
+}
+
+impl WindowsGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    // `rand()` of the MSVC runtime: a linear congruential generator returning 15 bits
+    fn rand(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(214013).wrapping_add(2531011);
+        (self.state >> 16) & 0x7fff
+    }
+}
+
+impl MineGenerator for WindowsGenerator {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+        let mut is_mine = vec![false; width as usize * height as usize];
+        let mut positions = Vec::with_capacity(mines_count as usize);
+        while positions.len() < mines_count as usize {
+            let x = (self.rand() % width as u32) as u16;
+            let y = (self.rand() % height as u32) as u16;
+            let index = y as usize * width as usize + x as usize;
+            if !is_mine[index] {
+                is_mine[index] = true;
+                positions.push((x, y));
+            }
+        }
+        positions
+    }
+}
+
+// Uniformly random layouts which never place a mine on the excluded cells, for levels
+// that need specific safe corridors. Panics if there are fewer free cells than mines
+#[derive(Debug, Clone, Default)]
+pub struct ExcludingGenerator {
+    pub excluded: HashSet<Position>,
+}
+
+impl ExcludingGenerator {
+    pub fn new(excluded: impl IntoIterator<Item = Position>) -> Self {
+        Self {
+            excluded: excluded.into_iter().collect(),
+        }
+    }
+}
+
+impl MineGenerator for ExcludingGenerator {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+        let free: Vec<Position> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|position| !self.excluded.contains(position))
+            .collect();
+        assert!(
+            free.len() >= mines_count as usize,
+            "Not enough cells outside the exclusion list"
+        );
+        free.choose_multiple(&mut rand::thread_rng(), mines_count as usize)
+            .copied()
+            .collect()
+    }
+}
+
+// Post-process of another generator which rejects claustrophobic boards: layouts without
+// an opening of at least `min_opening` cells. An opening is what clicking a cell without
+// mines around reveals. After `max_attempts` rejected layouts it gives up and returns the
+// layout with the largest opening, so impossible requirements don't hang the game
+#[derive(Debug, Clone, Copy)]
+pub struct PrettyGenerator<G> {
+    pub inner: G,
+    pub min_opening: usize,
+    pub max_attempts: u32,
+}
+
+impl<G: MineGenerator> PrettyGenerator<G> {
+    pub fn new(inner: G, min_opening: usize) -> Self {
+        Self {
+            inner,
+            min_opening,
+            max_attempts: 100,
+        }
+    }
+}
+
+impl<G: MineGenerator> MineGenerator for PrettyGenerator<G> {
+    fn generate(&mut self, width: u16, height: u16, mines_count: u16) -> Vec<Position> {
+        let mut best = (0, Vec::new());
+        for _ in 0..self.max_attempts.max(1) {
+            let positions = self.inner.generate(width, height, mines_count);
+            let opening = largest_opening(width, height, &positions);
+            if opening >= self.min_opening {
+                return positions;
+            }
+            if opening >= best.0 {
+                best = (opening, positions);
+            }
+        }
+        best.1
+    }
+}
+
+// Size of the largest opening of the layout: the connected cells without mines around
+// them together with the numbers bordering them
+pub fn largest_opening(width: u16, height: u16, mines: &[Position]) -> usize {
+    let (width, height) = (width as usize, height as usize);
+    let mut is_mine = vec![false; width * height];
+    mines
+        .iter()
+        .for_each(|&(x, y)| is_mine[y as usize * width + x as usize] = true);
+
+    let neighbours = |index: usize| {
+        let (x, y) = (index % width, index / width);
+        (x.saturating_sub(1)..=(x + 1).min(width - 1))
+            .flat_map(move |i| {
+                (y.saturating_sub(1)..=(y + 1).min(height - 1)).map(move |j| j * width + i)
+            })
+            .filter(move |&neighbour| neighbour != index)
+    };
+    let is_zero = |index: usize| !is_mine[index] && neighbours(index).all(|n| !is_mine[n]);
+
+    // Cells already counted in an opening, marked with the number of the opening
+    let mut seen = vec![0; width * height];
+    let mut largest = 0;
+    for start in 0..width * height {
+        if seen[start] != 0 || !is_zero(start) {
+            continue;
+        }
+        let mark = start + 1;
+        let mut size = 1;
+        seen[start] = mark;
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            for neighbour in neighbours(index) {
+                // Numbers may border several openings, so they are counted once per opening
+                if seen[neighbour] == mark || is_mine[neighbour] {
+                    continue;
+                }
+                seen[neighbour] = mark;
+                size += 1;
+                if is_zero(neighbour) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+    largest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Minesweeper;
+
+    #[test]
+    fn test_windows_rand() {
+        // The first values of `rand()` after `srand(1)` in the MSVC runtime
+        let mut generator = WindowsGenerator::new(1);
+        let values: Vec<u32> = (0..4).map(|_| generator.rand()).collect();
+        assert_eq!(values, vec![41, 18467, 6334, 26500]);
+    }
+
+    #[test]
+    fn test_windows_generator() {
+        // ================================================
+        // Test that the same seed gives the same board
+        let board = |seed| {
+            let mut positions = WindowsGenerator::new(seed).generate(9, 9, 10);
+            positions.sort();
+            positions
+        };
+        assert_eq!(board(42), board(42));
+        assert_ne!(board(42), board(43));
+
+        // ================================================
+        // Test the mines of a known seed: rand() gives 41 and 18467 first
+        let positions = WindowsGenerator::new(1).generate(9, 9, 1);
+        assert_eq!(positions, vec![(41 % 9, 18467 % 9)]);
+
+        let minesweeper = Minesweeper::with_generator(9, 9, 10, &mut WindowsGenerator::new(42));
+        assert_eq!(minesweeper.mines.len(), 10);
+    }
+
+    #[test]
+    fn test_random_generator() {
+        let positions = RandomGenerator.generate(3, 3, 8);
+        assert_eq!(positions.len(), 8);
+        assert!(positions.iter().all(|&(x, y)| x < 3 && y < 3));
+    }
+
+    #[test]
+    fn test_excluding_generator() {
+        // Exclude all cells but the last row
+        let excluded = (0..3).flat_map(|y| (0..4).map(move |x| (x, y)));
+        let mut generator = ExcludingGenerator::new(excluded);
+        for _ in 0..10 {
+            let minesweeper = Minesweeper::with_generator(4, 4, 3, &mut generator);
+            assert_eq!(minesweeper.mines.len(), 3);
+            assert!(minesweeper.mines.iter().all(|(_, y)| y == 3), "Last row");
+        }
+    }
+
+    #[test]
+    #[should_panic = "Not enough cells outside the exclusion list"]
+    fn test_excluding_generator_too_many_mines() {
+        ExcludingGenerator::new([(0, 0), (1, 0)]).generate(2, 2, 3);
+    }
+
+    #[test]
+    fn test_largest_opening() {
+        assert_eq!(
+            largest_opening(3, 3, &[(1, 1)]),
+            0,
+            "Every cell touches the mine"
+        );
+        // . . . . *      Zeros in the three left columns open 8 cells with the
+        // . . . . .      numbers next to them
+        assert_eq!(largest_opening(5, 2, &[(4, 0)]), 8);
+        assert_eq!(largest_opening(4, 4, &[]), 16, "Empty board");
+    }
+
+    #[test]
+    fn test_pretty_generator() {
+        // ================================================
+        // Test that layouts without a big enough opening are rejected
+        let mut generator = PrettyGenerator::new(WindowsGenerator::new(7), 20);
+        for _ in 0..10 {
+            let positions = generator.generate(9, 9, 10);
+            assert_eq!(positions.len(), 10);
+            assert!(largest_opening(9, 9, &positions) >= 20);
+        }
+
+        // ================================================
+        // Test that impossible requirements fall back to the best layout
+        let mut generator = PrettyGenerator {
+            inner: WindowsGenerator::new(7),
+            min_opening: 100,
+            max_attempts: 5,
+        };
+        assert_eq!(generator.generate(9, 9, 10).len(), 10);
+    }
+
+    #[test]
+    #[should_panic = "Duplicate mines"]
+    fn test_with_generator_duplicates() {
+        struct Stuck;
+        impl MineGenerator for Stuck {
+            fn generate(&mut self, _: u16, _: u16, mines_count: u16) -> Vec<Position> {
+                vec![(0, 0); mines_count as usize]
+            }
+        }
+        Minesweeper::with_generator(3, 3, 2, &mut Stuck);
+    }
+}
@@ -0,0 +1,203 @@
+// Turns raw pointer events into gestures, so every frontend interprets touches the same
+// way. A press released quickly on the same cell is a tap, a press held long enough is
+// a long press, and a second tap on the same cell shortly after the first one is a
+// double tap. Releasing the pointer over another cell cancels the press.
+use crate::{
+    action::Action,
+    model::{Minesweeper, Position},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureConfig {
+    // Minimal press duration of a long press
+    pub long_press_ms: u64,
+    // Maximal time between the ends of two taps of a double tap
+    pub double_tap_ms: u64,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            long_press_ms: 500,
+            double_tap_ms: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    Tap(Position),
+    LongPress(Position),
+    DoubleTap(Position),
+}
+
+// A gesture without its position, kept with the action it triggered in replays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureKind {
+    Tap,
+    LongPress,
+    DoubleTap,
+}
+
+impl Gesture {
+    pub fn kind(&self) -> GestureKind {
+        match self {
+            Gesture::Tap(_) => GestureKind::Tap,
+            Gesture::LongPress(_) => GestureKind::LongPress,
+            Gesture::DoubleTap(_) => GestureKind::DoubleTap,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GestureResolver {
+    config: GestureConfig,
+    // The pressed position and the time of the press
+    pressed: Option<(Position, u64)>,
+    // The position and the time of the last tap which can start a double tap
+    last_tap: Option<(Position, u64)>,
+}
+
+impl GestureResolver {
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    pub fn pointer_down(&mut self, position: Position, time_ms: u64) {
+        self.pressed = Some((position, time_ms));
+    }
+
+    // Resolve the gesture finished by releasing the pointer. Return None if the pointer
+    // wasn't pressed or was released over another cell
+    pub fn pointer_up(&mut self, position: Position, time_ms: u64) -> Option<Gesture> {
+        let (pressed, pressed_at) = self.pressed.take()?;
+        if pressed != position {
+            self.last_tap = None;
+            return None;
+        }
+
+        if time_ms.saturating_sub(pressed_at) >= self.config.long_press_ms {
+            self.last_tap = None;
+            return Some(Gesture::LongPress(position));
+        }
+
+        match self.last_tap.take() {
+            Some((last, tapped_at))
+                if last == position
+                    && time_ms.saturating_sub(tapped_at) <= self.config.double_tap_ms =>
+            {
+                Some(Gesture::DoubleTap(position))
+            }
+            _ => {
+                self.last_tap = Some((position, time_ms));
+                Some(Gesture::Tap(position))
+            }
+        }
+    }
+}
+
+impl Minesweeper {
+    // The action triggered by a gesture. A tap is the primary action and a long press is
+    // the secondary one. The first tap of a double tap already acted, so the second one
+    // chords the cell
+    pub fn gesture_action(&self, gesture: Gesture) -> Option<Action> {
+        match gesture {
+            Gesture::Tap(position) => Some(self.primary_action(position)),
+            Gesture::LongPress(position) => Some(self.secondary_action(position)),
+            Gesture::DoubleTap(position) => Some(Action::Chord(position)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_up() {
+        let mut resolver = GestureResolver::default();
+        assert_eq!(resolver.pointer_up((0, 0), 0), None, "Not pressed");
+
+        // ================================================
+        // Test taps and long presses
+        resolver.pointer_down((1, 1), 1000);
+        assert_eq!(
+            resolver.pointer_up((1, 1), 1100),
+            Some(Gesture::Tap((1, 1)))
+        );
+        resolver.pointer_down((2, 2), 2000);
+        assert_eq!(
+            resolver.pointer_up((2, 2), 2500),
+            Some(Gesture::LongPress((2, 2)))
+        );
+
+        // ================================================
+        // Test that releasing over another cell cancels the press
+        resolver.pointer_down((1, 1), 3000);
+        assert_eq!(resolver.pointer_up((1, 2), 3100), None, "Dragged away");
+
+        // ================================================
+        // Test double taps
+        resolver.pointer_down((1, 1), 4000);
+        assert_eq!(
+            resolver.pointer_up((1, 1), 4050),
+            Some(Gesture::Tap((1, 1)))
+        );
+        resolver.pointer_down((1, 1), 4200);
+        assert_eq!(
+            resolver.pointer_up((1, 1), 4250),
+            Some(Gesture::DoubleTap((1, 1)))
+        );
+        resolver.pointer_down((1, 1), 4300);
+        assert_eq!(
+            resolver.pointer_up((1, 1), 4350),
+            Some(Gesture::Tap((1, 1))),
+            "A double tap doesn't start another one"
+        );
+        resolver.pointer_down((1, 1), 5000);
+        assert_eq!(
+            resolver.pointer_up((1, 1), 5050),
+            Some(Gesture::Tap((1, 1))),
+            "Too late for a double tap"
+        );
+        resolver.pointer_down((2, 1), 5100);
+        assert_eq!(
+            resolver.pointer_up((2, 1), 5150),
+            Some(Gesture::Tap((2, 1))),
+            "Another cell"
+        );
+    }
+
+    #[test]
+    fn test_gesture_action() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        assert_eq!(
+            minesweeper.gesture_action(Gesture::Tap((1, 0))),
+            Some(Action::Open((1, 0)))
+        );
+        assert_eq!(
+            minesweeper.gesture_action(Gesture::LongPress((1, 0))),
+            Some(Action::ToggleFlag((1, 0)))
+        );
+        assert_eq!(
+            minesweeper.gesture_action(Gesture::DoubleTap((1, 0))),
+            Some(Action::Chord((1, 0)))
+        );
+
+        minesweeper.set_flag_mode(true);
+        assert_eq!(
+            minesweeper.gesture_action(Gesture::Tap((1, 0))),
+            Some(Action::ToggleFlag((1, 0))),
+            "Gestures respect flag mode"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+// Hash collections used on the hot paths of the game. Keys are small positions
+// which don't need DoS-resistant hashing, so the `fxhash` feature swaps the
+// default SipHash for the much faster FxHash
+#[cfg(feature = "fxhash")]
+pub type BuildHasher = rustc_hash::FxBuildHasher;
+#[cfg(not(feature = "fxhash"))]
+pub type BuildHasher = std::collections::hash_map::RandomState;
+
+pub type HashSet<T> = std::collections::HashSet<T, BuildHasher>;
+pub type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasher>;
@@ -0,0 +1,380 @@
+// Undo and redo. Every open, flag and chord is recorded as a `Move` holding only the
+// cells it changed, so the history stays small even on large boards. A move also keeps
+// the flag mode it was made in, which undo and redo bring back.
+//
+// To find the changed marks and mines, the marks and the layout from before the move are
+// kept as shared board data, see `Shared`. They are only copied if the move changes them
+use crate::{
+    action::Action,
+    confirmation::ConfirmationReason,
+    model::{Minesweeper, Position},
+    snapshot::Shared,
+};
+
+// A move with everything needed to take it back and to make it again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub action: Action,
+    // Cells opened by the move in opening order
+    opened: Vec<Position>,
+    // Whether the move flagged or unflagged the position of the action
+    flag_toggled: bool,
+    // Cells which got or lost a question mark
+    questions_toggled: Vec<Position>,
+    // Mines moved away from the first click, as pairs of the old and the new position
+    relocated: Vec<(Position, Position)>,
+    before: Counters,
+    after: Counters,
+    // Why the move had to be confirmed, see `Minesweeper::attempt`
+    confirmed: Option<ConfirmationReason>,
+    // Repeated inputs the debouncer ignored or turned into this move, see `Debouncer`
+    debounced: Vec<Action>,
+}
+
+impl Move {
+    pub fn opened(&self) -> &[Position] {
+        &self.opened
+    }
+
+    pub fn confirmed(&self) -> Option<ConfirmationReason> {
+        self.confirmed
+    }
+
+    pub fn debounced(&self) -> &[Action] {
+        &self.debounced
+    }
+
+    // Whether flag mode was on when the move was made, see `Minesweeper::set_flag_mode`
+    pub fn flag_mode(&self) -> bool {
+        self.before.flag_mode
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Counters {
+    moves: u32,
+    deductions: u32,
+    guesses: u32,
+    unlocked_regions: usize,
+    game_over: bool,
+    flag_mode: bool,
+}
+
+impl Counters {
+    fn new(minesweeper: &Minesweeper) -> Self {
+        Self {
+            moves: minesweeper.moves,
+            deductions: minesweeper.deductions,
+            guesses: minesweeper.guesses,
+            unlocked_regions: minesweeper.unlocked_regions,
+            game_over: minesweeper.game_over,
+            flag_mode: minesweeper.flag_mode,
+        }
+    }
+
+    fn restore(self, minesweeper: &mut Minesweeper) {
+        minesweeper.moves = self.moves;
+        minesweeper.deductions = self.deductions;
+        minesweeper.guesses = self.guesses;
+        minesweeper.unlocked_regions = self.unlocked_regions;
+        minesweeper.game_over = self.game_over;
+        minesweeper.flag_mode = self.flag_mode;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    done: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl History {
+    pub(crate) fn mark_confirmed(&mut self, reason: ConfirmationReason) {
+        if let Some(last) = self.done.last_mut() {
+            last.confirmed = Some(reason);
+        }
+    }
+}
+
+impl Minesweeper {
+    // Play an action and record it if it made a move. A new move can't be redone after,
+    // so it drops the undone moves
+    pub(crate) fn record<T>(&mut self, action: Action, play: impl FnOnce(&mut Self) -> T) -> T {
+        let position = action.position();
+        let before = Counters::new(self);
+        let flagged = self.flagged_positions.contains(&position);
+        let questioned = self.questioned_positions.clone();
+        let opened_before = self.opened_at.len();
+        // Only the first click moves mines, so the layout is only kept until then
+        let mines =
+            (self.safe_first_click() && self.layout_is_movable()).then(|| self.mines.clone());
+
+        let result = play(self);
+        if self.moves == before.moves {
+            return result;
+        }
+
+        let mut opened: Vec<(u32, Position)> = self
+            .opened_at
+            .iter()
+            .filter(|(_, &(_, ordinal))| ordinal as usize >= opened_before)
+            .map(|(&position, &(_, ordinal))| (ordinal, position))
+            .collect();
+        opened.sort();
+        let relocated = mines
+            .map(|mines| {
+                let removed = mines
+                    .iter()
+                    .filter(|position| !self.mines.contains(position));
+                let added = self
+                    .mines
+                    .iter()
+                    .filter(|position| !mines.contains(position));
+                removed.zip(added).collect()
+            })
+            .unwrap_or_default();
+
+        let mut questions_toggled: Vec<Position> = questioned
+            .symmetric_difference(&self.questioned_positions)
+            .copied()
+            .collect();
+        questions_toggled.sort();
+
+        let recorded = Move {
+            action,
+            opened: opened.into_iter().map(|(_, position)| position).collect(),
+            flag_toggled: flagged != self.flagged_positions.contains(&position),
+            questions_toggled,
+            relocated,
+            before,
+            after: Counters::new(self),
+            confirmed: None,
+            debounced: Vec::new(),
+        };
+        self.history.done.push(recorded);
+        self.history.undone.clear();
+        self.pending_confirmation = None;
+        self.observe_timer();
+        result
+    }
+
+    // The moves which can be undone, oldest first
+    pub fn history(&self) -> &[Move] {
+        &self.history.done
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.undone.is_empty()
+    }
+
+    // Take back the last move, even one that lost the game. Return false if there is
+    // nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let Some(last) = self.history.done.pop() else {
+            return false;
+        };
+
+        last.opened.iter().for_each(|position| {
+            self.open_positions.remove(position);
+            self.opened_at.remove(position);
+        });
+        if last.flag_toggled {
+            self.toggle_flag_of(last.action.position());
+        }
+        last.questions_toggled
+            .iter()
+            .for_each(|&position| self.toggle_question_of(position));
+        last.relocated.iter().for_each(|&(from, to)| {
+            self.mines.remove(&to);
+            self.mines.insert(from);
+        });
+        last.before.restore(self);
+        self.pending_confirmation = None;
+        self.observe_timer();
+        self.debug_audit();
+
+        self.history.undone.push(last);
+        true
+    }
+
+    // Make the last undone move again. Return false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.history.undone.pop() else {
+            return false;
+        };
+
+        next.relocated.iter().for_each(|&(from, to)| {
+            self.mines.remove(&from);
+            self.mines.insert(to);
+        });
+        if next.flag_toggled {
+            self.toggle_flag_of(next.action.position());
+        }
+        next.questions_toggled
+            .iter()
+            .for_each(|&position| self.toggle_question_of(position));
+        next.opened.iter().for_each(|&position| {
+            self.open_positions.insert(position);
+            let ordinal = self.opened_at.len() as u32;
+            self.opened_at
+                .insert(position, (next.before.moves, ordinal));
+        });
+        next.after.restore(self);
+        self.pending_confirmation = None;
+        self.observe_timer();
+        self.debug_audit();
+
+        self.history.done.push(next);
+        true
+    }
+
+    // Note an input the debouncer ignored or coalesced on the last move, so the history
+    // shows what the player actually did
+    pub fn record_debounced(&mut self, action: Action) {
+        if let Some(last) = self.history.done.last_mut() {
+            last.debounced.push(action);
+        }
+    }
+
+    pub(crate) fn clear_history(&mut self) {
+        self.history = Shared::default();
+    }
+
+    fn toggle_flag_of(&mut self, position: Position) {
+        if !self.flagged_positions.remove(&position) {
+            self.flagged_positions.insert(position);
+        }
+    }
+
+    fn toggle_question_of(&mut self, position: Position) {
+        if !self.questioned_positions.remove(&position) {
+            self.questioned_positions.insert(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CellState, GameStatus};
+
+    #[test]
+    fn test_undo_redo() {
+        let mut minesweeper = crate::board!["*..", "...", "...", "..*"];
+        assert!(!minesweeper.undo(), "Nothing to undo");
+
+        // ================================================
+        // Test that a cascade and a flag are taken back and made again
+        minesweeper.open((2, 0)).toggle_flag((0, 0));
+        let played = minesweeper.to_string();
+        assert_eq!(minesweeper.history().len(), 2, "2 moves recorded");
+        assert_eq!(minesweeper.history()[0].opened().len(), 6, "Whole cascade");
+
+        assert!(minesweeper.undo() && minesweeper.undo());
+        assert_eq!(minesweeper.moves(), 0, "No moves left");
+        assert!(minesweeper.open_positions.is_empty(), "Cascade taken back");
+        assert!(minesweeper.flagged_positions.is_empty(), "Flag taken back");
+        assert!(!minesweeper.can_undo() && minesweeper.can_redo());
+
+        assert!(minesweeper.redo() && minesweeper.redo());
+        assert!(!minesweeper.redo(), "Nothing to redo");
+        assert_eq!(minesweeper.to_string(), played, "Same board");
+        assert_eq!(minesweeper.moves(), 2, "Same moves");
+        assert_eq!(
+            minesweeper.open_ordinal((2, 0)),
+            Some(0),
+            "Same opening order"
+        );
+
+        // ================================================
+        // Test that a new move drops the undone moves
+        minesweeper.undo();
+        minesweeper.open((0, 1));
+        assert!(!minesweeper.can_redo(), "Undone flag dropped");
+    }
+
+    #[test]
+    fn test_undo_flag_mode() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_flag_mode(true);
+        minesweeper.apply(minesweeper.primary_action((0, 0)));
+        minesweeper.set_flag_mode(false);
+        minesweeper.apply(minesweeper.primary_action((2, 0)));
+        assert!(minesweeper.history()[0].flag_mode(), "Flagged in flag mode");
+        assert!(!minesweeper.history()[1].flag_mode());
+
+        // ================================================
+        // Test that undo and redo bring back the mode of the move
+        minesweeper.undo();
+        minesweeper.undo();
+        assert!(minesweeper.flag_mode(), "Mode of the flag");
+        minesweeper.redo();
+        assert!(minesweeper.flag_mode());
+        minesweeper.redo();
+        assert!(!minesweeper.flag_mode(), "Mode of the open");
+    }
+
+    #[test]
+    fn test_undo_loss() {
+        let mut minesweeper = crate::board!["*o.", "oo.", "..."];
+        minesweeper.toggle_flag((2, 0));
+        minesweeper.chord((1, 0));
+        assert_eq!(minesweeper.status(), GameStatus::Lost);
+
+        // ================================================
+        // Test that the losing chord is taken back
+        minesweeper.undo();
+        assert_eq!(minesweeper.status(), GameStatus::InProgress);
+        assert_eq!(minesweeper.cell_state((0, 0)), CellState::Hidden);
+        assert_eq!(minesweeper.cell_state((2, 1)), CellState::Hidden);
+
+        // ================================================
+        // Test that a forced open restores the flag it cleared
+        minesweeper.force_open((2, 0));
+        assert_eq!(minesweeper.cell_state((2, 0)), CellState::Open(0));
+        minesweeper.undo();
+        assert_eq!(minesweeper.cell_state((2, 0)), CellState::Flagged);
+    }
+
+    #[test]
+    fn test_undo_first_click() {
+        // ================================================
+        // Test that the mines moved by the first click are moved back
+        let mut minesweeper = Minesweeper::from_mines(4, 4, [(0, 0), (1, 1)]);
+        minesweeper.set_safe_first_click(true);
+        minesweeper.open((0, 0));
+        assert!(!minesweeper.mines.contains(&(0, 0)), "Mine moved away");
+        let mut mines: Vec<Position> = minesweeper.mines.iter().collect();
+        mines.sort();
+
+        minesweeper.undo();
+        assert!(minesweeper.mines.contains(&(0, 0)) && minesweeper.mines.contains(&(1, 1)));
+        minesweeper.redo();
+        let mut redone: Vec<Position> = minesweeper.mines.iter().collect();
+        redone.sort();
+        assert_eq!(redone, mines, "Same layout");
+
+        // ================================================
+        // Test that the first click can't be taken back once the layout is committed
+        let mut committed = minesweeper.sandbox();
+        let commitment = committed.commit();
+        assert!(!committed.undo(), "History cleared");
+        committed.commit();
+        committed.open(mines[0]);
+        let reveal = committed.reveal().unwrap();
+        assert!(crate::fairness::verify_commitment(&commitment, &reveal));
+
+        // ================================================
+        // Test that a probe fixes the layout like a committed one
+        minesweeper.undo();
+        minesweeper.set_probes(1);
+        minesweeper.probe((3, 3));
+        minesweeper.undo();
+        minesweeper.open((0, 0));
+        assert!(minesweeper.mines.contains(&(0, 0)), "Mine not moved");
+    }
+}
@@ -1,38 +1,694 @@
+pub mod action;
+pub mod annotation;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod aria;
+pub mod audit;
+pub mod board_info;
+pub mod clock;
+pub mod compression;
+pub mod confirmation;
+pub mod debounce;
+pub mod diff;
+pub mod fairness;
+pub mod feedback;
+pub mod game_state;
+pub mod generator;
+pub mod gesture;
+pub mod hash;
+pub mod history;
+pub mod locale;
+pub mod luck;
+pub mod mine_field;
 pub mod model;
+pub mod multi_board;
+pub mod narration;
+pub mod opening;
+pub mod orientation;
+pub mod partition;
+pub mod probe;
+pub mod region;
+pub mod risk;
+pub mod save;
+pub mod seed_phrase;
+pub mod selection;
+pub mod snapshot;
+pub mod spectator;
+pub mod summary;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod text_format;
+pub mod theme;
+pub mod timer;
+pub mod typescript;
+pub mod validation;
+pub mod zoom;
 
 use std::cell::RefCell;
 
+use action::{Action, TimedAction};
+use aria::AriaGrid;
+use board_info::BoardInfo;
+use clock::SystemClock;
+use confirmation::{Attempt, ConfirmationPolicy};
+use debounce::Debouncer;
+use fairness::BoardReveal;
+use game_state::GameState;
+use gesture::{GestureConfig, GestureResolver};
+use locale::Digits;
 use model::Minesweeper;
+use narration::{Language, Message};
+use region::Region;
+use summary::ResultSummary;
+use text_format::TextState;
+use theme::ThemePreset;
+// The bindings are only generated for the browser target, see Cargo.toml
+#[cfg(target_os = "unknown")]
 use wasm_bindgen::prelude::*;
+use zoom::Zoom;
 
 // This is like a global variable, but it's only accessible from the current thread.
 // JS is single-threaded, so this is fine.
 thread_local! {
     static MS: RefCell<Minesweeper> = RefCell::new(Minesweeper::new(10, 10, 15));
+    static RENDER_CACHE: RefCell<RenderCache> = RefCell::default();
+    static GESTURES: RefCell<GestureResolver> = RefCell::default();
+    static DEBOUNCER: RefCell<Debouncer<SystemClock>> =
+        RefCell::new(Debouncer::new(SystemClock::new(), 0));
 }
 
-#[wasm_bindgen(js_name = "getGameState")]
-pub fn get_game_state() -> String {
-    MS.with_borrow(|ms| ms.to_string())
+// Renders of the current state. Frontends poll the state every frame, so the renders
+// are only rebuilt after something actually changed
+#[derive(Default)]
+struct RenderCache {
+    game_state: Option<String>,
+    game_state_v2: Option<String>,
+    game_state_json: Option<String>,
+}
+
+fn invalidate_render_cache() {
+    RENDER_CACHE.set(RenderCache::default());
+}
+
+fn cached_render(
+    slot: fn(&mut RenderCache) -> &mut Option<String>,
+    render: fn(&Minesweeper) -> String,
+) -> String {
+    RENDER_CACHE.with_borrow_mut(|cache| {
+        slot(cache)
+            .get_or_insert_with(|| MS.with_borrow(render))
+            .clone()
+    })
 }
 
-#[wasm_bindgen(js_name = "openCell")]
-pub fn open_cell(x: usize, y: usize) {
+// Apply a player action to the game. Actions that were rejected don't count as a move,
+// so the cached renders are kept
+fn play<R>(action: impl FnOnce(&mut Minesweeper) -> R) -> R {
     MS.with_borrow_mut(|ms| {
-        ms.open((x as u16, y as u16));
+        let moves = ms.moves();
+        let result = action(ms);
+        if ms.moves() != moves {
+            invalidate_render_cache();
+        }
+        result
+    })
+}
+
+// Play the action unless the debouncer ignores it as a duplicate, which is recorded in the
+// history instead. A double click on an open number is played as a chord. Return None if
+// the action was ignored
+fn debounced<R>(action: Action, apply: impl FnOnce(&mut Minesweeper, Action) -> R) -> Option<R> {
+    let accepted =
+        MS.with_borrow(|ms| DEBOUNCER.with_borrow_mut(|debouncer| debouncer.accept(action, ms)));
+    let Some(accepted) = accepted else {
+        MS.with_borrow_mut(|ms| ms.record_debounced(action));
+        return None;
+    };
+    Some(play(|ms| {
+        let moves = ms.moves();
+        let result = apply(ms, accepted);
+        if accepted != action && ms.moves() != moves {
+            ms.record_debounced(action);
+        }
+        result
+    }))
+}
+
+// Change a display setting of the game. Always drops the cached renders
+fn configure(setting: impl FnOnce(&mut Minesweeper)) {
+    MS.with_borrow_mut(setting);
+    invalidate_render_cache();
+}
+
+// Replace the game with a new one of the given size, e.g. for difficulty presets. Return
+// false and keep the current game if the parameters are invalid
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "newGame"))]
+pub fn new_game(width: usize, height: usize, mines: usize) -> bool {
+    start_game(width, height, mines, Minesweeper::new)
+}
+
+// Like `newGame`, but the same seed always gives the same board, e.g. for daily
+// challenges and bug reports
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "newGameWithSeed"))]
+pub fn new_game_with_seed(width: usize, height: usize, mines: usize, seed: u64) -> bool {
+    start_game(width, height, mines, |width, height, mines| {
+        Minesweeper::new_with_seed(width, height, mines, seed)
+    })
+}
+
+// Return the seed of the game, or undefined if the board isn't seeded
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getSeed"))]
+pub fn get_seed() -> Option<u64> {
+    MS.with_borrow(|ms| ms.seed())
+}
+
+fn start_game(
+    width: usize,
+    height: usize,
+    mines: usize,
+    create: impl FnOnce(u16, u16, u16) -> Minesweeper,
+) -> bool {
+    let (Ok(width), Ok(height), Ok(mines)) = (
+        u16::try_from(width),
+        u16::try_from(height),
+        u16::try_from(mines),
+    ) else {
+        return false;
+    };
+    if !Minesweeper::are_valid_parameters(width, height, mines) {
+        return false;
+    }
+
+    replace_game(create(width, height, mines));
+    true
+}
+
+// Return the game as JSON which `loadGame` restores, e.g. after a page refresh. The JSON
+// holds the mines, so keep it out of sight of the player
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "saveGame"))]
+pub fn save_game() -> String {
+    MS.with_borrow(|ms| ms.serialize())
+}
+
+// Replace the game with a saved one. Return false and keep the current game if the save
+// is invalid
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "loadGame"))]
+pub fn load_game(state: &str) -> bool {
+    match Minesweeper::deserialize(state) {
+        Ok(minesweeper) => {
+            replace_game(minesweeper);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn replace_game(minesweeper: Minesweeper) {
+    MS.set(minesweeper);
+    GESTURES.with_borrow_mut(|gestures| *gestures = GestureResolver::new(gestures.config()));
+    DEBOUNCER.with_borrow_mut(|debouncer| debouncer.reset());
+    invalidate_render_cache();
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameState"))]
+pub fn get_game_state() -> String {
+    cached_render(|cache| &mut cache.game_state, |ms| ms.to_string())
+}
+
+// The board drawn with every cell as a block of glyphs
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameStateZoomed"))]
+pub fn get_game_state_zoomed(zoom: Zoom) -> String {
+    MS.with_borrow(|ms| ms.render_zoomed(zoom))
+}
+
+// Game state with the digits of the locale, mirrored for right-to-left layouts. The
+// other exports keep counting x from the left edge
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameStateLocalized"))]
+pub fn get_game_state_localized(digits: Digits, rtl: bool) -> String {
+    MS.with_borrow(|ms| ms.render_localized(digits, rtl))
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getBoardInfo"))]
+pub fn get_board_info() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&BoardInfo::new(ms)).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameStateV2"))]
+pub fn get_game_state_v2() -> String {
+    cached_render(
+        |cache| &mut cache.game_state_v2,
+        |ms| TextState::new(ms).to_string(),
+    )
+}
+
+// Return the status of the game as JSON: "in_progress", "won" or "lost"
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameStatus"))]
+pub fn get_game_status() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.status()).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getGameStateJson"))]
+pub fn get_game_state_json() -> String {
+    cached_render(
+        |cache| &mut cache.game_state_json,
+        |ms| serde_json::to_string(&GameState::new(ms)).unwrap_or_default(),
+    )
+}
+
+// Tag a cell with a short note, or remove the tag with an empty one. Return false if the
+// cell is outside the board or the tag is too long
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "annotateCell"))]
+pub fn annotate_cell(x: usize, y: usize, tag: &str) -> bool {
+    let mut annotated = false;
+    configure(|ms| annotated = ms.annotate((x as u16, y as u16), tag));
+    annotated
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getAnnotations"))]
+pub fn get_annotations() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.annotations()).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getAriaGrid"))]
+pub fn get_aria_grid() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&AriaGrid::new(ms)).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getAriaGridLocalized"))]
+pub fn get_aria_grid_localized(language: Language) -> String {
+    MS.with_borrow(|ms| {
+        serde_json::to_string(&AriaGrid::localized(ms, language)).unwrap_or_default()
+    })
+}
+
+// Mines minus flags for the classic mine counter, negative when there are more flags
+// than mines
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getRemainingMines"))]
+pub fn get_remaining_mines() -> i32 {
+    MS.with_borrow(|ms| ms.remaining_mines())
+}
+
+// Refuse flags beyond the mine count, so the mine counter never goes negative
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setFlagCap"))]
+pub fn set_flag_cap(flag_cap: bool) {
+    MS.with_borrow_mut(|ms| ms.set_flag_cap(flag_cap));
+}
+
+// Announcement of the mines left to flag, e.g. "3 mines remain"
+#[cfg_attr(
+    target_os = "unknown",
+    wasm_bindgen(js_name = "getRemainingMinesMessage")
+)]
+pub fn get_remaining_mines_message(language: Language) -> String {
+    MS.with_borrow(|ms| language.narrate(Message::MinesRemain(ms.remaining_mines())))
+}
+
+// Return the frontier components and the sea of unknown cells as JSON
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getPartition"))]
+pub fn get_partition() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.partition()).unwrap_or_default())
+}
+
+// Return what the player could know about every hidden cell as JSON rows, for
+// spectator overlays
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getSpectatorOverlay"))]
+pub fn get_spectator_overlay() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.spectator_overlay()).unwrap_or_default())
+}
+
+// Return a coarse risk rating of every frontier cell as JSON rows, for players who turn
+// the mine detector assist on
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getRiskMap"))]
+pub fn get_risk_map() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.risk_map()).unwrap_or_default())
+}
+
+// Return the recommended first click as JSON, or null once the game has started
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "suggestOpening"))]
+pub fn suggest_opening() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.suggest_opening()).unwrap_or_default())
+}
+
+// Describe the surroundings of the cursor for audio play as JSON, with up to `limit`
+// nearest frontier cells
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getOrientation"))]
+pub fn get_orientation(x: usize, y: usize, limit: usize) -> String {
+    MS.with_borrow(|ms| {
+        let orientation = ms.orientation((x as u16, y as u16), limit);
+        serde_json::to_string(&orientation).unwrap_or_default()
+    })
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getLuckStats"))]
+pub fn get_luck_stats() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.luck_stats()).unwrap_or_default())
+}
+
+// Return the open ordinal of every cell in row-major order as JSON, null for cells
+// which weren't opened
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getOpenOrder"))]
+pub fn get_open_order() -> String {
+    MS.with_borrow(|ms| {
+        let ordinals: Vec<Option<u32>> = (0..ms.height())
+            .flat_map(|y| (0..ms.width()).map(move |x| (x, y)))
+            .map(|position| ms.open_ordinal(position))
+            .collect();
+        serde_json::to_string(&ordinals).unwrap_or_default()
+    })
+}
+
+// Summary of the finished game with the time of the timer
+fn timed_result_summary() -> Option<ResultSummary> {
+    MS.with_borrow(|ms| {
+        ms.result_summary()
+            .map(|summary| summary.with_time(ms.elapsed_ms()))
+    })
+}
+
+// Summary of the finished game as JSON, or null while the game is in progress
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getResultSummary"))]
+pub fn get_result_summary() -> String {
+    serde_json::to_string(&timed_result_summary()).unwrap_or_default()
+}
+
+// Text of the finished game to share, or undefined while the game is in progress
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getResultCard"))]
+pub fn get_result_card() -> Option<String> {
+    timed_result_summary().map(|summary| summary.card())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "takeFeedbackEvents"))]
+pub fn take_feedback_events() -> String {
+    MS.with_borrow_mut(|ms| serde_json::to_string(&ms.take_feedback_events()).unwrap_or_default())
+}
+
+// Return the changed cells as JSON, like the other opening bindings
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "openCell"))]
+pub fn open_cell(x: usize, y: usize) -> String {
+    play_with_changes(Action::Open((x as u16, y as u16)))
+}
+
+// Open the unflagged neighbours of an open number whose flags match it
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "chordCell"))]
+pub fn chord_cell(x: usize, y: usize) -> String {
+    play_with_changes(Action::Chord((x as u16, y as u16)))
+}
+
+// Open the cell even if it is flagged, after the player confirmed it
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "forceOpen"))]
+pub fn force_open(x: usize, y: usize) -> String {
+    play_with_changes(Action::ForceOpen((x as u16, y as u16)))
+}
+
+// Open every hidden, unflagged cell of the rectangle as a single move. Nothing is opened
+// if one of the cells is a proven mine
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "openRegion"))]
+pub fn open_region(x: usize, y: usize, width: usize, height: usize) -> String {
+    play_with_changes(Action::OpenRegion(selection::Rect {
+        x: x as u16,
+        y: y as u16,
+        width: width as u16,
+        height: height as u16,
+    }))
+}
+
+// Choose which actions wait for a confirmation, see `attemptAction`
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setConfirmationPolicy"))]
+pub fn set_confirmation_policy(policy: ConfirmationPolicy) {
+    MS.with_borrow_mut(|ms| ms.set_confirmation_policy(policy));
+}
+
+// Play an action, given as JSON, unless the confirmation policy asks to confirm it first.
+// Return the Attempt as JSON, or null if the action can't be parsed
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "attemptAction"))]
+pub fn attempt_action(action: &str) -> String {
+    let Ok(action) = serde_json::from_str::<Action>(action) else {
+        return "null".to_string();
+    };
+    let attempt = debounced(action, |ms, action| ms.attempt(action)).unwrap_or(Attempt::Debounced);
+    serde_json::to_string(&attempt).unwrap_or_default()
+}
+
+// Play the action waiting for the confirmation with the token. Return the cells it
+// changed as JSON, or null if the token is no longer valid
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "confirmAction"))]
+pub fn confirm_action(token: u32) -> String {
+    play(|ms| serde_json::to_string(&ms.confirm(token)).unwrap_or_default())
+}
+
+// Play the action unless it is debounced and return the cells it changed as JSON, so the
+// frontend updates only those
+fn play_with_changes(action: Action) -> String {
+    let changes = debounced(action, |ms, action| ms.apply_with_changes(action));
+    serde_json::to_string(&changes.unwrap_or_default()).unwrap_or_default()
+}
+
+// Milliseconds played, counted from the first open cell until the game is over and
+// without the pauses
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getElapsedMs"))]
+pub fn get_elapsed_ms() -> f64 {
+    MS.with_borrow(|ms| ms.elapsed_ms() as f64)
+}
+
+// Return false if the timer isn't running
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "pauseTimer"))]
+pub fn pause_timer() -> bool {
+    MS.with_borrow_mut(|ms| ms.pause())
+}
+
+// Return false if the timer isn't paused
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "resumeTimer"))]
+pub fn resume_timer() -> bool {
+    MS.with_borrow_mut(|ms| ms.resume())
+}
+
+// Take back the last move. Return false if there is nothing to undo
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "undoMove"))]
+pub fn undo_move() -> bool {
+    play(|ms| ms.undo())
+}
+
+// Make the last undone move again. Return false if there is nothing to redo
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "redoMove"))]
+pub fn redo_move() -> bool {
+    play(|ms| ms.redo())
+}
+
+// Cycle the mark of the cell through a flag, a question mark and no mark. Return the
+// FlagToggle as JSON, or null if the cell can't be marked or the cycle was ignored as a
+// duplicate
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "cycleMark"))]
+pub fn cycle_mark(x: usize, y: usize) -> String {
+    let position = (x as u16, y as u16);
+    let result = debounced(Action::CycleMark(position), |ms, _| ms.cycle_mark(position)).flatten();
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+// Return the new mark of the cell and the remaining mines as JSON, or null if the cell
+// can't be flagged or the toggle was ignored as a duplicate
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "toggleFlag"))]
+pub fn toggle_flag(x: usize, y: usize) -> String {
+    let position = (x as u16, y as u16);
+    let result = debounced(Action::ToggleFlag(position), |ms, _| {
+        ms.toggle_flag(position)
+    })
+    .flatten();
+    serde_json::to_string(&result).unwrap_or_default()
+}
+
+// Divide the board into regions which unlock in order, given as a JSON array. Return
+// false if the regions are invalid
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setRegions"))]
+pub fn set_regions(regions: &str) -> bool {
+    let valid = serde_json::from_str::<Vec<Region>>(regions)
+        .is_ok_and(|regions| MS.with_borrow_mut(|ms| ms.set_regions(regions).is_ok()));
+    invalidate_render_cache();
+    valid
+}
+
+// Return the name and the state of every region as JSON
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getRegions"))]
+pub fn get_regions() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.region_statuses()).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setProbes"))]
+pub fn set_probes(probes: u32) {
+    MS.with_borrow_mut(|ms| ms.set_probes(probes));
+}
+
+// Spend a probe on the cell and return "mine" or "safe" as JSON, or null if the cell
+// can't be probed
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "probeCell"))]
+pub fn probe_cell(x: usize, y: usize) -> String {
+    play(|ms| serde_json::to_string(&ms.probe((x as u16, y as u16))).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setFlagMode"))]
+pub fn set_flag_mode(flag_mode: bool) {
+    MS.with_borrow_mut(|ms| ms.set_flag_mode(flag_mode));
+}
+
+// Click or tap: opens the cell, or toggles its flag in flag mode
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "primaryAction"))]
+pub fn primary_action(x: usize, y: usize) {
+    let action = MS.with_borrow(|ms| ms.primary_action((x as u16, y as u16)));
+    debounced(action, |ms, action| {
+        ms.apply(action);
     });
 }
 
-#[wasm_bindgen(js_name = "toggleFlag")]
-pub fn toggle_flag(x: usize, y: usize) {
-    MS.with_borrow_mut(|ms| {
-        ms.toggle_flag((x as u16, y as u16));
+// Right click or long press: toggles the flag of the cell, or opens it in flag mode
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "secondaryAction"))]
+pub fn secondary_action(x: usize, y: usize) {
+    let action = MS.with_borrow(|ms| ms.secondary_action((x as u16, y as u16)));
+    debounced(action, |ms, action| {
+        ms.apply(action);
+    });
+}
+
+// Apply recorded actions, given as a JSON array of TimedAction, in a single call. Return
+// the BatchResult as JSON, or null if the actions can't be parsed
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "applyActions"))]
+pub fn apply_actions(actions: &str) -> String {
+    let Ok(actions) = serde_json::from_str::<Vec<TimedAction>>(actions) else {
+        return "null".to_string();
+    };
+    play(|ms| serde_json::to_string(&ms.apply_actions(&actions)).unwrap_or_default())
+}
+
+// Ignore repeats of the same action within the given number of milliseconds, which
+// filters out the jitter of touchscreens. Zero turns the debouncing off
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setDebounce"))]
+pub fn set_debounce(window_ms: u32) {
+    DEBOUNCER.with_borrow_mut(|debouncer| debouncer.set_window_ms(window_ms as u64));
+}
+
+// Return how many actions the debouncer ignored as duplicates
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getIgnoredInputs"))]
+pub fn get_ignored_inputs() -> u32 {
+    DEBOUNCER.with_borrow(|debouncer| debouncer.ignored())
+}
+
+// Set how long a press must be held to be a long press and how soon a second tap must
+// follow the first one to be a double tap, in milliseconds. Drops a press in progress
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setGestureConfig"))]
+pub fn set_gesture_config(long_press_ms: u32, double_tap_ms: u32) {
+    GESTURES.set(GestureResolver::new(GestureConfig {
+        long_press_ms: long_press_ms as u64,
+        double_tap_ms: double_tap_ms as u64,
+    }));
+}
+
+// Feed raw pointer events with their timestamps in milliseconds. Releasing the pointer
+// resolves the gesture and applies its action. It returns the action with the time and
+// the gesture as a TimedAction in JSON, for the replay, or null if it didn't make a move
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "pointerDown"))]
+pub fn pointer_down(x: usize, y: usize, time_ms: f64) {
+    GESTURES
+        .with_borrow_mut(|gestures| gestures.pointer_down((x as u16, y as u16), time_ms as u64));
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "pointerUp"))]
+pub fn pointer_up(x: usize, y: usize, time_ms: f64) -> String {
+    let gesture = GESTURES
+        .with_borrow_mut(|gestures| gestures.pointer_up((x as u16, y as u16), time_ms as u64));
+    let timed = gesture.and_then(|gesture| {
+        let action = MS.with_borrow(|ms| ms.gesture_action(gesture))?;
+        let played = debounced(action, |ms, action| {
+            let moves = ms.moves();
+            (ms.apply(action).moves() != moves).then_some(action)
+        });
+        played.flatten().map(|action| TimedAction {
+            action,
+            time_ms: time_ms as u64,
+            gesture: Some(gesture.kind()),
+        })
     });
+    serde_json::to_string(&timed).unwrap_or_default()
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setTheme"))]
+pub fn set_theme(preset: ThemePreset) {
+    configure(|ms| ms.set_theme(preset.theme()));
+}
+
+// Select a built-in theme by its name. Return false if there is no such theme
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setThemeByName"))]
+pub fn set_theme_by_name(name: &str) -> bool {
+    ThemePreset::from_name(name)
+        .map(|preset| configure(|ms| ms.set_theme(preset.theme())))
+        .is_some()
+}
+
+// Return the names of all built-in themes as a JSON array
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "getThemeNames"))]
+pub fn get_theme_names() -> String {
+    let names: Vec<&str> = ThemePreset::ALL
+        .iter()
+        .map(|preset| preset.name())
+        .collect();
+    serde_json::to_string(&names).unwrap_or_default()
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "setBlindMode"))]
+pub fn set_blind_mode(moves: Option<u32>) {
+    configure(|ms| ms.set_blind_mode(moves));
+}
+
+// Commit to the board layout and return the SHA-256 commitment in hex
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "commitBoard"))]
+pub fn commit_board() -> String {
+    MS.with_borrow_mut(|ms| ms.commit())
+}
+
+// Return the layout and the nonce as JSON once the game is over, or null
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "revealBoard"))]
+pub fn reveal_board() -> String {
+    MS.with_borrow(|ms| serde_json::to_string(&ms.reveal()).unwrap_or_default())
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "encodeSeed"))]
+pub fn encode_seed(seed: u64) -> String {
+    seed_phrase::encode_seed(seed)
+}
+
+// Return the seed of a phrase, or undefined if the phrase is invalid
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "decodeSeed"))]
+pub fn decode_seed(phrase: &str) -> Option<u64> {
+    seed_phrase::decode_seed(phrase).ok()
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "verifyCommitment"))]
+pub fn verify_commitment(commitment: &str, reveal: &str) -> bool {
+    serde_json::from_str::<BoardReveal>(reveal)
+        .is_ok_and(|reveal| fairness::verify_commitment(commitment, &reveal))
+}
+
+// Replay a submitted game on its revealed layout and return the verdict as JSON, or
+// null if the reveal or the replay can't be parsed
+#[cfg_attr(target_os = "unknown", wasm_bindgen(js_name = "validateSubmission"))]
+pub fn validate_submission(
+    commitment: &str,
+    reveal: &str,
+    replay: &str,
+    claimed_time_ms: u64,
+) -> String {
+    let (Ok(reveal), Ok(replay)) = (
+        serde_json::from_str::<BoardReveal>(reveal),
+        serde_json::from_str::<Vec<TimedAction>>(replay),
+    ) else {
+        return "null".to_string();
+    };
+    let verdict = validation::validate_submission(commitment, &reveal, &replay, claimed_time_ms);
+    serde_json::to_string(&verdict).unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clock::MockClock;
+    use std::sync::Arc;
 
     #[test]
     fn test_get_game_state() {
@@ -42,6 +698,290 @@ mod tests {
         assert_eq!(lines[0].chars().count(), 20);
     }
 
+    #[test]
+    fn test_new_game() {
+        open_cell(0, 0);
+        let state = get_game_state();
+
+        // ================================================
+        // Test that invalid parameters keep the current game
+        assert!(!new_game(0, 10, 10), "Empty board");
+        assert!(!new_game(3, 3, 9), "No safe cell");
+        assert!(!new_game(70_000, 1, 1), "Too wide");
+        assert!(!new_game(65_535, 65_535, 10), "Too large");
+        assert_eq!(get_game_state(), state, "Same game");
+
+        // ================================================
+        // Test that a valid game replaces the current one
+        assert!(new_game(30, 16, 99), "Expert");
+        let info: serde_json::Value = serde_json::from_str(&get_board_info()).unwrap();
+        assert_eq!(
+            (info["width"].as_u64(), info["height"].as_u64()),
+            (Some(30), Some(16))
+        );
+        assert_eq!(info["mines"], 99);
+        assert_eq!(get_game_state().lines().count(), 16, "Render isn't stale");
+        MS.with_borrow(|ms| assert_eq!(ms.moves(), 0, "No moves yet"));
+        assert_eq!(get_seed(), None, "Not seeded");
+    }
+
+    #[test]
+    fn test_new_game_with_seed() {
+        assert!(!new_game_with_seed(3, 3, 9, 42), "No safe cell");
+        assert!(new_game_with_seed(16, 16, 40, 42));
+        assert_eq!(get_seed(), Some(42));
+        let state = get_game_state_json();
+        open_cell(5, 5);
+        let opened = get_game_state();
+
+        // ================================================
+        // Test that the same seed and click give the same board
+        assert!(new_game_with_seed(16, 16, 40, 42));
+        assert_eq!(get_game_state_json(), state, "Same game");
+        open_cell(5, 5);
+        assert_eq!(get_game_state(), opened, "Same first click");
+    }
+
+    #[test]
+    fn test_save_load_game() {
+        let clock = Arc::new(MockClock::new(0));
+        MS.with_borrow_mut(|ms| ms.set_clock(clock.clone()));
+        open_cell(4, 4);
+        clock.advance(5_000);
+        let save = save_game();
+        let state = get_game_state();
+
+        // ================================================
+        // Test that a saved game is restored and an invalid save is ignored
+        assert!(new_game(5, 5, 3));
+        assert!(!load_game("{}"), "Invalid save");
+        assert!(load_game(&save));
+        assert_eq!(get_game_state(), state, "Render isn't stale");
+        assert!(get_elapsed_ms() >= 5_000.0, "Time kept");
+    }
+
+    #[test]
+    fn test_get_game_state_zoomed() {
+        let state = get_game_state_zoomed(Zoom::Triple);
+        assert_eq!(state.lines().count(), 30);
+        assert_eq!(get_game_state_zoomed(Zoom::Normal), get_game_state());
+    }
+
+    #[test]
+    fn test_get_game_state_localized() {
+        let state = get_game_state_localized(Digits::Persian, true);
+        assert_eq!(state.lines().count(), 10);
+        assert_eq!(
+            get_game_state_localized(Digits::Latin, false),
+            get_game_state()
+        );
+    }
+
+    #[test]
+    fn test_get_board_info() {
+        let info: serde_json::Value = serde_json::from_str(&get_board_info()).unwrap();
+        assert_eq!(info["width"], 10);
+        assert_eq!(info["height"], 10);
+        assert_eq!(info["mines"], 15);
+    }
+
+    #[test]
+    fn test_get_game_state_v2() {
+        let state = TextState::parse(&get_game_state_v2()).unwrap();
+        assert_eq!((state.width, state.height), (10, 10));
+        assert_eq!(state.cells.len(), 100);
+    }
+
+    #[test]
+    fn test_get_game_status() {
+        assert_eq!(get_game_status(), r#""in_progress""#);
+        MS.with_borrow_mut(|ms| {
+            // Keep the layout, so the safe cells are known up front
+            ms.set_safe_first_click(false);
+            let safe: Vec<_> = (0..10)
+                .flat_map(|y| (0..10).map(move |x| (x, y)))
+                .filter(|position| !ms.mines.contains(position))
+                .collect();
+            safe.into_iter().for_each(|position| {
+                ms.open(position);
+            });
+        });
+        assert_eq!(get_game_status(), r#""won""#);
+    }
+
+    #[test]
+    fn test_get_game_state_json() {
+        let state: serde_json::Value = serde_json::from_str(&get_game_state_json()).unwrap();
+        assert_eq!(state["remaining_mines"], 15);
+        assert_eq!(state["rows"].as_array().unwrap().len(), 10);
+        assert_eq!(state["rows"][0][0]["state"], "hidden");
+
+        // ================================================
+        // Test that the cached state is rebuilt after a move
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        toggle_flag(mine.0 as usize, mine.1 as usize);
+        let state: serde_json::Value = serde_json::from_str(&get_game_state_json()).unwrap();
+        assert_eq!(state["remaining_mines"], 14);
+        assert_eq!(
+            state["rows"][mine.1 as usize][mine.0 as usize]["state"],
+            "flagged"
+        );
+
+        // ================================================
+        // Test that the cached state is rebuilt after an annotation
+        assert!(annotate_cell(1, 2, "red"));
+        assert!(!annotate_cell(10, 0, "red"), "Outside the board");
+        let state: serde_json::Value = serde_json::from_str(&get_game_state_json()).unwrap();
+        assert_eq!(state["annotations"][0]["tag"], "red");
+        assert_eq!(get_annotations(), r#"[{"position":[1,2],"tag":"red"}]"#);
+    }
+
+    #[test]
+    fn test_get_risk_map() {
+        let risks: serde_json::Value = serde_json::from_str(&get_risk_map()).unwrap();
+        assert_eq!(risks.as_array().unwrap().len(), 10);
+        assert!(risks[0][0].is_null(), "No frontier before the first click");
+    }
+
+    #[test]
+    fn test_get_spectator_overlay() {
+        let overlay: serde_json::Value = serde_json::from_str(&get_spectator_overlay()).unwrap();
+        assert_eq!(overlay.as_array().unwrap().len(), 10);
+        assert_eq!(
+            overlay[0][0], "unknown",
+            "Nothing is known before the first click"
+        );
+    }
+
+    #[test]
+    fn test_get_aria_grid() {
+        let grid: serde_json::Value = serde_json::from_str(&get_aria_grid()).unwrap();
+        assert_eq!(grid["role"], "grid");
+        assert_eq!(grid["rows"].as_array().unwrap().len(), 10);
+        assert_eq!(grid["rows"][0]["cells"].as_array().unwrap().len(), 10);
+
+        let grid: serde_json::Value =
+            serde_json::from_str(&get_aria_grid_localized(Language::Russian)).unwrap();
+        assert_eq!(grid["label"], "Поле сапёра, столбцов: 10, строк: 10");
+    }
+
+    #[test]
+    fn test_get_remaining_mines() {
+        assert_eq!(get_remaining_mines(), 15);
+        set_flag_cap(true);
+        for x in 0..10 {
+            for y in 0..2 {
+                toggle_flag(x, y);
+            }
+        }
+        assert_eq!(get_remaining_mines(), 0, "Flags capped");
+    }
+
+    #[test]
+    fn test_get_remaining_mines_message() {
+        assert_eq!(
+            get_remaining_mines_message(Language::English),
+            "15 mines remain"
+        );
+        assert_eq!(
+            get_remaining_mines_message(Language::Russian),
+            "осталось 15 мин"
+        );
+    }
+
+    #[test]
+    fn test_get_partition() {
+        let partition: serde_json::Value = serde_json::from_str(&get_partition()).unwrap();
+        assert_eq!(
+            partition["sea"].as_array().unwrap().len(),
+            100,
+            "Nothing is open"
+        );
+        assert_eq!(partition["sea_min_mines"], 15);
+    }
+
+    #[test]
+    fn test_suggest_opening() {
+        let opening: Option<(u16, u16)> = serde_json::from_str(&suggest_opening()).unwrap();
+        let (x, y) = opening.expect("Game not started");
+        open_cell(x as usize, y as usize);
+        assert_eq!(suggest_opening(), "null", "Game started");
+    }
+
+    #[test]
+    fn test_get_orientation() {
+        let orientation: serde_json::Value =
+            serde_json::from_str(&get_orientation(0, 0, 5)).unwrap();
+        assert_eq!(orientation["label"], "hidden");
+        assert_eq!(orientation["neighbours"].as_array().unwrap().len(), 3);
+        assert_eq!(orientation["neighbours"][0]["direction"], "east");
+    }
+
+    #[test]
+    fn test_get_open_order() {
+        let cell = MS.with_borrow(|ms| (0..10).find(|&x| !ms.mines.contains(&(x, 0))).unwrap());
+        open_cell(cell as usize, 0);
+        let ordinals: Vec<Option<u32>> = serde_json::from_str(&get_open_order()).unwrap();
+        assert_eq!(ordinals.len(), 100);
+        assert_eq!(ordinals[cell as usize], Some(0), "First opened cell");
+    }
+
+    #[test]
+    fn test_get_result_summary() {
+        assert_eq!(get_result_summary(), "null", "Game in progress");
+        assert_eq!(get_result_card(), None);
+
+        // Lose on the first click
+        MS.with_borrow_mut(|ms| ms.set_safe_first_click(false));
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        open_cell(mine.0 as usize, mine.1 as usize);
+        let summary: serde_json::Value = serde_json::from_str(&get_result_summary()).unwrap();
+        assert_eq!(summary["status"], "lost");
+        assert!(summary["elapsed_ms"].is_u64(), "Time added");
+        assert!(get_result_card().unwrap().contains("💥"));
+    }
+
+    #[test]
+    fn test_get_luck_stats() {
+        open_cell(0, 0);
+        let stats: serde_json::Value = serde_json::from_str(&get_luck_stats()).unwrap();
+        assert_eq!(stats["guesses"], 1, "First click is a guess");
+        assert_eq!(stats["luck_ratio"], 1.0);
+    }
+
+    #[test]
+    fn test_commit_reveal() {
+        let commitment = commit_board();
+        assert_eq!(reveal_board(), "null", "Game in progress");
+
+        MS.with_borrow_mut(|ms| {
+            let mine = ms.mines.iter().next().unwrap();
+            ms.open(mine);
+        });
+        let reveal = reveal_board();
+        assert!(verify_commitment(&commitment, &reveal), "Valid commitment");
+        assert!(!verify_commitment(&commitment, "{}"), "Invalid reveal");
+        assert_eq!(
+            validate_submission(&commitment, &reveal, "[]", 0),
+            r#"{"verdict":"not_won"}"#
+        );
+        assert_eq!(validate_submission(&commitment, &reveal, "{}", 0), "null");
+    }
+
+    #[test]
+    fn test_seed_phrases() {
+        assert_eq!(decode_seed(&encode_seed(42)), Some(42));
+        assert_eq!(decode_seed("not a phrase"), None);
+    }
+
+    #[test]
+    fn test_set_theme_by_name() {
+        assert!(set_theme_by_name("ascii"), "Theme exists");
+        assert!(get_game_state().starts_with("# # "), "ASCII theme is used");
+        assert!(!set_theme_by_name("neon"), "No such theme");
+        assert!(get_theme_names().contains(r#""classic-windows""#));
+    }
+
     #[test]
     fn test_open_cell() {
         MS.with_borrow_mut(|ms| {
@@ -57,6 +997,19 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_open_cell_changes() {
+        let changes: serde_json::Value = serde_json::from_str(&open_cell(4, 4)).unwrap();
+        let opened = MS.with_borrow(|ms| ms.open_positions.len());
+        assert_eq!(
+            changes.as_array().unwrap().len(),
+            opened,
+            "Every opened cell"
+        );
+        assert_eq!(changes[0]["after"]["state"], "open");
+        assert_eq!(open_cell(4, 4), "[]", "Already open");
+    }
+
     #[test]
     fn test_toggle_flag() {
         MS.with_borrow_mut(|ms| {
@@ -69,5 +1022,231 @@ mod tests {
             }
             assert_eq!(ms.flagged_positions.len(), 15, "15 cells should be flagged");
         });
+
+        let result: serde_json::Value = serde_json::from_str(&toggle_flag(20, 20)).unwrap();
+        assert!(result.is_null(), "Cell outside the board can't be flagged");
+    }
+
+    #[test]
+    fn test_apply_actions() {
+        assert_eq!(apply_actions("[{"), "null", "Invalid JSON");
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        let actions = format!(
+            r#"[{{"action":{{"toggle_flag":[{x},{y}]}},"time_ms":1}},{{"action":{{"open":[{x},{y}]}},"time_ms":2}}]"#,
+            x = mine.0,
+            y = mine.1
+        );
+        let result: serde_json::Value = serde_json::from_str(&apply_actions(&actions)).unwrap();
+        assert_eq!(
+            result["status"], "rolled_back",
+            "Flagged cell can't be opened"
+        );
+        assert_eq!(result["index"], 1);
+        MS.with_borrow(|ms| assert_eq!(ms.moves(), 0, "Nothing applied"));
+    }
+
+    #[test]
+    fn test_attempt_action() {
+        set_confirmation_policy(ConfirmationPolicy::Risky);
+        assert_eq!(attempt_action("{\"dig\":[0,0]}"), "null", "Unknown action");
+        let attempt = attempt_action(r#"{"open":[4,4]}"#);
+        assert!(
+            attempt.starts_with(r#"{"outcome":"played","changes":[{"#),
+            "First click: {attempt}"
+        );
+        assert_eq!(confirm_action(1), "null", "Nothing to confirm");
+
+        // ================================================
+        // Test that a debounced action has an outcome of its own
+        let (x, y) = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        let flag = format!(r#"{{"toggle_flag":[{x},{y}]}}"#);
+        set_debounce(60_000);
+        assert!(attempt_action(&flag).starts_with(r#"{"outcome":"played""#));
+        assert_eq!(attempt_action(&flag), r#"{"outcome":"debounced"}"#);
+    }
+
+    #[test]
+    fn test_timer() {
+        let clock = Arc::new(MockClock::new(0));
+        MS.with_borrow_mut(|ms| ms.set_clock(clock.clone()));
+        assert!(!pause_timer(), "Not started");
+        open_cell(4, 4);
+        clock.advance(300);
+        assert!(pause_timer());
+        clock.advance(5_000);
+        assert_eq!(get_elapsed_ms(), 300.0, "Paused");
+        assert!(resume_timer());
+        clock.advance(200);
+        assert_eq!(get_elapsed_ms(), 500.0);
+
+        // ================================================
+        // Test that a new game resets the timer
+        assert!(new_game(9, 9, 10));
+        assert_eq!(get_elapsed_ms(), 0.0);
+        assert!(!resume_timer(), "Not started");
+    }
+
+    #[test]
+    fn test_undo_redo_move() {
+        assert!(!undo_move(), "Nothing to undo");
+        let state = get_game_state();
+        toggle_flag(0, 0);
+        assert!(undo_move());
+        assert_eq!(get_game_state(), state, "Render cache invalidated");
+        assert!(redo_move());
+        assert!(!redo_move(), "Nothing to redo");
+        MS.with_borrow(|ms| assert!(ms.flagged_positions.contains(&(0, 0))));
+    }
+
+    #[test]
+    fn test_set_debounce() {
+        let (x, y) = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        let (x, y) = (x as usize, y as usize);
+        set_debounce(60_000);
+        assert_ne!(toggle_flag(x, y), "null", "First toggle is accepted");
+        assert_eq!(toggle_flag(x, y), "null", "Duplicate toggle is ignored");
+        assert!(MS.with_borrow(|ms| ms.flagged_positions.contains(&(x as u16, y as u16))));
+        assert_eq!(get_ignored_inputs(), 1);
+        MS.with_borrow(|ms| {
+            let last = ms.history().last().unwrap();
+            assert_eq!(last.debounced(), [last.action], "Recorded in history");
+        });
+
+        set_debounce(0);
+        assert_ne!(toggle_flag(x, y), "null", "Debouncing is off");
+        assert!(MS.with_borrow(|ms| ms.flagged_positions.is_empty()));
+    }
+
+    #[test]
+    fn test_double_click_chord() {
+        replace_game(crate::board!["*o.", "...", "..."]);
+        toggle_flag(0, 0);
+        set_debounce(60_000);
+
+        // ================================================
+        // Test that a double click on an open number chords it
+        assert_eq!(open_cell(1, 0), "[]", "Already open");
+        assert_ne!(open_cell(1, 0), "[]", "Chorded");
+        MS.with_borrow(|ms| {
+            let last = ms.history().last().unwrap();
+            assert_eq!(last.action, Action::Chord((1, 0)));
+            assert_eq!(last.debounced(), [Action::Open((1, 0))], "Coalesced");
+        });
+        assert_eq!(get_ignored_inputs(), 0, "Nothing ignored");
+    }
+
+    #[test]
+    fn test_render_cache() {
+        let state = get_game_state();
+        get_game_state_v2();
+        let is_cached = || {
+            RENDER_CACHE
+                .with_borrow(|cache| cache.game_state.is_some() && cache.game_state_v2.is_some())
+        };
+        assert!(is_cached(), "Renders are cached");
+
+        // ================================================
+        // Test that a rejected action keeps the cache
+        toggle_flag(20, 20);
+        assert!(is_cached(), "Nothing changed");
+
+        // ================================================
+        // Test that actions and settings drop the cache
+        toggle_flag(0, 0);
+        assert!(!is_cached(), "Cell flagged");
+        assert_ne!(get_game_state(), state, "Flag is rendered");
+        get_game_state_v2();
+        set_theme(ThemePreset::Ascii);
+        assert!(!is_cached(), "Theme changed");
+        assert!(get_game_state().starts_with("F "), "ASCII theme is used");
+    }
+
+    #[test]
+    fn test_regions() {
+        assert_eq!(get_regions(), "[]");
+        let regions = r#"[
+            {"name": "north", "x": 0, "y": 0, "width": 10, "height": 5},
+            {"name": "south", "x": 0, "y": 5, "width": 10, "height": 5}
+        ]"#;
+        get_game_state_json();
+        assert!(set_regions(regions));
+        let statuses: serde_json::Value = serde_json::from_str(&get_regions()).unwrap();
+        assert_eq!(statuses[0]["name"], "north");
+        assert_eq!(statuses[1]["state"], "locked");
+        let state: serde_json::Value = serde_json::from_str(&get_game_state_json()).unwrap();
+        assert_eq!(state["regions"], statuses, "In the state export");
+        assert!(!set_regions("[{}]"), "Invalid JSON");
+    }
+
+    #[test]
+    fn test_probe_cell() {
+        assert_eq!(probe_cell(0, 0), "null", "No probes");
+        set_probes(1);
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        assert_eq!(probe_cell(mine.0 as usize, mine.1 as usize), r#""mine""#);
+        assert!(get_board_info().contains(r#""probes_left":0"#));
+    }
+
+    #[test]
+    fn test_flag_mode() {
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        set_flag_mode(true);
+        assert!(get_board_info().contains(r#""flag_mode":true"#));
+        primary_action(mine.0 as usize, mine.1 as usize);
+        MS.with_borrow(|ms| assert!(ms.flagged_positions.contains(&mine), "Mine flagged"));
+
+        set_flag_mode(false);
+        secondary_action(mine.0 as usize, mine.1 as usize);
+        MS.with_borrow(|ms| assert!(ms.flagged_positions.is_empty(), "Flag removed"));
+    }
+
+    #[test]
+    fn test_pointer_events() {
+        let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+        let (x, y) = (mine.0 as usize, mine.1 as usize);
+        pointer_down(x, y, 1000.0);
+        let flag = pointer_up(x, y, 1600.0);
+        MS.with_borrow(|ms| assert!(ms.flagged_positions.contains(&mine), "Long press flags"));
+        assert_eq!(
+            flag,
+            format!(
+                r#"{{"action":{{"toggle_flag":[{x},{y}]}},"time_ms":1600,"gesture":"long_press"}}"#
+            ),
+            "Recorded for the replay"
+        );
+        let replay = [serde_json::from_str::<TimedAction>(&flag).unwrap()];
+        let decoded = compression::decode_replay(&compression::encode_replay(&replay));
+        assert_eq!(decoded, Ok(replay.to_vec()), "Gesture kept in the encoding");
+
+        pointer_down(x, y, 2000.0);
+        assert_eq!(pointer_up(x, y, 2050.0), "null", "No move");
+        MS.with_borrow(|ms| assert!(!ms.game_over, "Flagged mine can't be opened"));
+
+        // ================================================
+        // Test that gestures go through the debouncer
+        set_debounce(60_000);
+        pointer_down(x, y, 3000.0);
+        pointer_up(x, y, 3600.0);
+        pointer_down(x, y, 4000.0);
+        pointer_up(x, y, 4600.0);
+        MS.with_borrow(|ms| assert!(ms.flagged_positions.is_empty(), "Repeat is ignored"));
+    }
+
+    #[test]
+    fn test_set_gesture_config() {
+        let long_press = || {
+            let mine = MS.with_borrow(|ms| ms.mines.iter().next().unwrap());
+            let (x, y) = (mine.0 as usize, mine.1 as usize);
+            pointer_down(x, y, 1000.0);
+            pointer_up(x, y, 1150.0);
+            MS.with_borrow(|ms| ms.flagged_positions.contains(&mine))
+        };
+        set_gesture_config(100, 300);
+        assert!(long_press(), "Held longer than configured");
+
+        // ================================================
+        // Test that a new game keeps the configuration
+        assert!(new_game(9, 9, 10));
+        assert!(long_press());
     }
 }
@@ -0,0 +1,103 @@
+// Localized text rendering for frontends in other scripts. Numbers can be rendered with
+// the digits of the locale, and right-to-left layouts get the board mirrored along the
+// x axis. Only the rendering changes: positions passed to the engine keep counting x
+// from the left edge. The other exports, like the JSON state, the text format and the
+// ARIA grid, aren't mirrored, since their positions are read back by frontends. They
+// are drawn right to left by the frontend, e.g. with `dir="rtl"`.
+use crate::model::Minesweeper;
+use std::fmt::Write;
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Digits {
+    #[default]
+    Latin,
+    // ٠١٢٣٤٥٦٧٨٩
+    EasternArabic,
+    // ۰۱۲۳۴۵۶۷۸۹
+    Persian,
+    // ०१२३४५६७८९
+    Devanagari,
+}
+
+impl Digits {
+    fn zero(self) -> char {
+        match self {
+            Digits::Latin => '0',
+            Digits::EasternArabic => '\u{660}',
+            Digits::Persian => '\u{6f0}',
+            Digits::Devanagari => '\u{966}',
+        }
+    }
+
+    // Replace the digits of a glyph made only of ASCII digits. Other glyphs, like keycap
+    // emoji which are built on ASCII digits, are kept as they are
+    pub fn localize(self, glyph: &str) -> String {
+        if glyph.is_empty() || !glyph.bytes().all(|b| b.is_ascii_digit()) {
+            return glyph.to_string();
+        }
+        glyph
+            .bytes()
+            .filter_map(|b| char::from_u32(self.zero() as u32 + (b - b'0') as u32))
+            .collect()
+    }
+}
+
+impl Minesweeper {
+    pub fn render_localized(&self, digits: Digits, rtl: bool) -> String {
+        let theme = self.theme();
+        let mut rendered = String::new();
+        for y in 0..self.height() {
+            for i in 0..self.width() {
+                let x = if rtl { self.width() - 1 - i } else { i };
+                let glyph = digits.localize(theme.glyph(self.cell_state((x, y))));
+                // Writing to a string can't fail
+                let _ = write!(rendered, "{glyph} ");
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_localize() {
+        assert_eq!(Digits::Latin.localize("3"), "3");
+        assert_eq!(Digits::EasternArabic.localize("3"), "٣");
+        assert_eq!(Digits::Persian.localize("0"), "۰");
+        assert_eq!(Digits::Devanagari.localize("8"), "८");
+        assert_eq!(
+            Digits::EasternArabic.localize("3\u{fe0f}\u{20e3}"),
+            "3\u{fe0f}\u{20e3}",
+            "Keycaps are kept"
+        );
+        assert_eq!(Digits::EasternArabic.localize("#"), "#", "Not a number");
+    }
+
+    #[test]
+    fn test_render_localized() {
+        let mut minesweeper = crate::board!["**.", "...", "..*"];
+        minesweeper.open((2, 0));
+        assert_eq!(
+            minesweeper.render_localized(Digits::Latin, false),
+            minesweeper.to_string(),
+            "Latin digits from left to right is the regular board"
+        );
+
+        // ================================================
+        // Test mirrored rows with localized digits
+        minesweeper.set_theme(Theme::ASCII);
+        assert_eq!(minesweeper.to_string(), "# # 1 \n# # # \n# # # \n");
+        assert_eq!(
+            minesweeper.render_localized(Digits::EasternArabic, true),
+            "١ # # \n# # # \n# # # \n"
+        );
+    }
+}
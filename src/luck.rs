@@ -0,0 +1,58 @@
+use crate::model::Minesweeper;
+use serde::Serialize;
+
+// How many opens the numbers on the board proved safe and how many were guesses. A high
+// luck ratio means the game was decided by fortune rather than deduction
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LuckStats {
+    pub deductions: u32,
+    pub guesses: u32,
+    // Share of guesses among all opens, 0 before the first open
+    pub luck_ratio: f64,
+}
+
+impl Minesweeper {
+    pub fn luck_stats(&self) -> LuckStats {
+        let opens = self.deductions + self.guesses;
+        LuckStats {
+            deductions: self.deductions,
+            guesses: self.guesses,
+            luck_ratio: if opens == 0 {
+                0.0
+            } else {
+                self.guesses as f64 / opens as f64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luck_stats() {
+        let mut minesweeper = crate::board!["*...", "....", "...*"];
+        assert_eq!(minesweeper.luck_stats().luck_ratio, 0.0, "Nothing opened");
+
+        // ================================================
+        // Test that the first click is a guess and a proven cell is a deduction
+        //
+        //     * 1 . .
+        //     1 1 . .
+        //     . . . *
+        minesweeper.open((1, 0)).open((0, 1)).open((1, 1));
+        assert_eq!(minesweeper.luck_stats().guesses, 3, "Nothing proven yet");
+        minesweeper.open((2, 0));
+        minesweeper.open((1, 0));
+        assert_eq!(
+            minesweeper.luck_stats(),
+            LuckStats {
+                deductions: 1,
+                guesses: 3,
+                luck_ratio: 0.75,
+            },
+            "Opening an open cell doesn't count"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use rand::Rng;
+use std::{
+    collections::hash_set,
+    fmt::{Debug, Formatter},
+};
+
+use crate::{hash::HashSet, model::Position};
+
+// Set of mine positions which never holds a position in plain form. Every position is
+// packed into a u32 and XOR-masked with a random per-game key, so scanning the wasm
+// memory for coordinates doesn't reveal the mines
+#[derive(Clone)]
+pub struct MineField {
+    key: u32,
+    masked: HashSet<u32>,
+}
+
+impl MineField {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            key: rand::thread_rng().gen(),
+            masked: HashSet::with_capacity_and_hasher(capacity, Default::default()),
+        }
+    }
+
+    pub fn insert(&mut self, position: Position) -> bool {
+        self.masked.insert(mask(position, self.key))
+    }
+
+    pub fn remove(&mut self, position: &Position) -> bool {
+        self.masked.remove(&mask(*position, self.key))
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        self.masked.contains(&mask(*position, self.key))
+    }
+
+    pub fn len(&self) -> usize {
+        self.masked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masked.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.masked.iter().map(|&masked| unmask(masked, self.key))
+    }
+
+    // Remove all mines. Like `HashSet::drain`, the set is cleared even if the
+    // returned iterator is dropped without being consumed
+    pub fn drain(&mut self) -> Drain<'_> {
+        Drain {
+            key: self.key,
+            inner: self.masked.drain(),
+        }
+    }
+}
+
+impl Default for MineField {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Don't leak the positions through debug output
+impl Debug for MineField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MineField")
+            .field("len", &self.masked.len())
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Drain<'a> {
+    key: u32,
+    inner: hash_set::Drain<'a, u32>,
+}
+
+impl Iterator for Drain<'_> {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Position> {
+        self.inner.next().map(|masked| unmask(masked, self.key))
+    }
+}
+
+fn mask((x, y): Position, key: u32) -> u32 {
+    ((x as u32) << 16 | y as u32) ^ key
+}
+
+fn unmask(masked: u32, key: u32) -> Position {
+    let packed = masked ^ key;
+    ((packed >> 16) as u16, packed as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask() {
+        let key = 0xdead_beef;
+        let position = (12, 345);
+        assert_ne!(mask(position, key), (12 << 16) | 345, "Position is masked");
+        assert_eq!(unmask(mask(position, key), key), position, "Round trip");
+    }
+
+    #[test]
+    fn test_mine_field() {
+        let mut mines = MineField::new();
+        assert!(mines.is_empty(), "No mines");
+
+        // ================================================
+        // Test insertion and lookup
+        assert!(mines.insert((1, 2)), "Mine inserted");
+        assert!(!mines.insert((1, 2)), "Mine already inserted");
+        mines.insert((3, 4));
+        assert_eq!(mines.len(), 2, "2 mines");
+        assert!(mines.contains(&(1, 2)));
+        assert!(!mines.contains(&(2, 1)));
+        let mut positions: Vec<Position> = mines.iter().collect();
+        positions.sort();
+        assert_eq!(positions, vec![(1, 2), (3, 4)]);
+
+        // ================================================
+        // Test that debug output doesn't reveal the positions
+        let debug = format!("{mines:?}");
+        assert!(!debug.contains('3') && !debug.contains('4'), "{debug}");
+
+        // ================================================
+        // Test removal
+        assert!(mines.remove(&(1, 2)), "Mine removed");
+        assert!(!mines.remove(&(1, 2)), "Mine already removed");
+        assert_eq!(mines.drain().collect::<Vec<_>>(), vec![(3, 4)]);
+        assert!(mines.is_empty(), "All mines drained");
+    }
+}
@@ -1,100 +1,535 @@
-use rand::Rng;
-use std::{
-    collections::HashSet,
-    fmt::{Display, Formatter, Write},
+use crate::{
+    action::Action,
+    clock::SharedClock,
+    confirmation::{ConfirmationPolicy, PendingConfirmation},
+    feedback::FeedbackEvent,
+    generator::{MineGenerator, RandomGenerator, SeededGenerator},
+    hash::{HashMap, HashSet},
+    history::History,
+    mine_field::MineField,
+    region::Region,
+    snapshot::Shared,
+    theme::Theme,
+    timer::Timer,
 };
-
-const CELL: char = '🟨';
-const FLAG: &str = "🇷🇺";
-const MINE: char = '💣';
-const EXPLOSION: char = '💥';
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use std::fmt::{Display, Formatter, Write};
 
 pub type Position = (u16, u16);
 
+// Longest side of a board. It keeps the cells of a game in the millions, so a custom
+// board from the frontend can't allocate gigabytes
+pub const MAX_BOARD_SIDE: u16 = 1_024;
+
+// The state of a cell as the player sees it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellState {
+    Hidden,
+    Flagged,
+    // A hidden cell the player marked with a question mark
+    Questioned,
+    // An open cell with the number of mines around it
+    Open(u8),
+    // An open cell whose number is hidden in blind mode
+    Masked,
+    Mine,
+    Exploded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    InProgress,
+    Won,
+    Lost,
+}
+
+// The mark of a cell after it was toggled or cycled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellMark {
+    Flagged,
+    Questioned,
+    Cleared,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FlagToggle {
+    pub mark: CellMark,
+    // Mines minus flags, negative when there are more flags than mines
+    pub remaining_mines: i32,
+}
+
 #[derive(Debug, PartialEq)]
 enum OpeningResult {
     Mine,
     NoMine(u8),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Minesweeper {
     width: u16,
     height: u16,
-    pub open_positions: HashSet<Position>,
-    pub mines: HashSet<Position>,
-    pub flagged_positions: HashSet<Position>,
+    pub open_positions: Shared<HashSet<Position>>,
+    pub mines: Shared<MineField>,
+    pub flagged_positions: Shared<HashSet<Position>>,
+    // Hidden cells the player is unsure about, see `Minesweeper::cycle_mark`
+    pub(crate) questioned_positions: Shared<HashSet<Position>>,
     pub game_over: bool,
+    theme: Theme,
+    feedback_events: Vec<FeedbackEvent>,
+    // Number of moves which changed the board
+    pub(crate) moves: u32,
+    // The move at which every position was opened and its ordinal among all opened cells
+    pub(crate) opened_at: Shared<HashMap<Position, (u32, u32)>>,
+    // In blind mode, numbers are only shown for this many subsequent moves
+    blind_window: Option<u32>,
+    // In flag mode the primary action flags cells instead of opening them
+    pub(crate) flag_mode: bool,
+    // Opens which the numbers proved safe and opens which were guesses
+    pub(crate) deductions: u32,
+    pub(crate) guesses: u32,
+    // Probes the player may still spend and probes already spent
+    pub(crate) probes_left: u32,
+    pub(crate) probes_used: u32,
+    // Campaign regions in unlock order and the number of playable ones
+    pub(crate) regions: Vec<Region>,
+    pub(crate) unlocked_regions: usize,
+    // Secret nonce of the fairness commitment
+    pub(crate) commitment_nonce: Option<String>,
+    // Move mines away from the first opened cell, so the first click never loses
+    safe_first_click: bool,
+    // Moves which can be undone and moves which can be redone
+    pub(crate) history: Shared<History>,
+    // Tags the player put on cells, see `Minesweeper::annotate`
+    pub(crate) annotations: HashMap<Position, String>,
+    // Seed of the layout and of the safe first click, see `Minesweeper::new_with_seed`
+    pub(crate) seed: Option<u64>,
+    // Refuse flags beyond the mine count, so the mine counter never goes negative
+    flag_cap: bool,
+    // Actions which wait for a confirmation, the last prompt and the number of prompts,
+    // see `Minesweeper::attempt`
+    pub(crate) confirmation_policy: ConfirmationPolicy,
+    pub(crate) pending_confirmation: Option<PendingConfirmation>,
+    pub(crate) tokens_issued: u32,
+    // Time played, see `Minesweeper::elapsed_ms`
+    pub(crate) timer: Timer<SharedClock>,
 }
 
 impl Minesweeper {
+    // Create a game with a random layout and a safe first click
     pub fn new(width: u16, height: u16, mines_count: u16) -> Self {
-        // Check if the parameters are valid
+        let mut minesweeper =
+            Self::with_generator(width, height, mines_count, &mut RandomGenerator);
+        minesweeper.safe_first_click = true;
+        minesweeper
+    }
+
+    // Create a game like `new`, but reproducible: the same seed gives the same layout, and
+    // the same first click moves the same mines
+    pub fn new_with_seed(width: u16, height: u16, mines_count: u16, seed: u64) -> Self {
+        let mut generator = SeededGenerator::new(seed);
+        let mut minesweeper = Self::with_generator(width, height, mines_count, &mut generator);
+        minesweeper.safe_first_click = true;
+        minesweeper.seed = Some(seed);
+        minesweeper
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    // Create a game with mines placed by the given generator
+    pub fn with_generator(
+        width: u16,
+        height: u16,
+        mines_count: u16,
+        generator: &mut impl MineGenerator,
+    ) -> Self {
         assert!(
-            width > 0 && height > 0 && mines_count > 0 && mines_count < width * height,
+            Self::are_valid_parameters(width, height, mines_count),
             "Invalid parameters"
         );
 
-        // Convert mines_count to usize to convert it to usize only once
-        let mines_count = mines_count as usize;
+        let positions = generator.generate(width, height, mines_count);
+        let mut mines = MineField::with_capacity(mines_count as usize);
+        positions.into_iter().for_each(|(x, y)| {
+            assert!(x < width && y < height, "Mine outside the board");
+            mines.insert((x, y));
+        });
+        assert_eq!(mines.len(), mines_count as usize, "Duplicate mines");
+
+        Self::with_mine_field(width, height, mines)
+    }
+
+    // Check the parameters of a new game: a non-empty board no larger than
+    // `MAX_BOARD_SIDE` on each side, with at least one mine and at least one safe cell
+    pub fn are_valid_parameters(width: u16, height: u16, mines_count: u16) -> bool {
+        // Multiply in u32, the area of a large board doesn't fit in u16
+        (1..=MAX_BOARD_SIDE).contains(&width)
+            && (1..=MAX_BOARD_SIDE).contains(&height)
+            && mines_count > 0
+            && (mines_count as u32) < width as u32 * height as u32
+    }
+
+    // Create a game with mines at the given positions
+    pub fn from_mines(
+        width: u16,
+        height: u16,
+        positions: impl IntoIterator<Item = Position>,
+    ) -> Self {
+        assert!(width > 0 && height > 0, "Invalid parameters");
+
+        let mut mines = MineField::new();
+        positions.into_iter().for_each(|(x, y)| {
+            assert!(x < width && y < height, "Mine outside the board");
+            mines.insert((x, y));
+        });
+
+        Self::with_mine_field(width, height, mines)
+    }
 
+    fn with_mine_field(width: u16, height: u16, mines: MineField) -> Self {
         Self {
             width,
             height,
-            open_positions: HashSet::with_capacity(width as usize * height as usize - mines_count),
-            flagged_positions: HashSet::new(),
+            open_positions: HashSet::with_capacity_and_hasher(
+                width as usize * height as usize - mines.len(),
+                Default::default(),
+            )
+            .into(),
+            flagged_positions: Shared::default(),
+            questioned_positions: Shared::default(),
             game_over: false,
-            mines: {
-                let mut mines = HashSet::with_capacity(mines_count);
-                while mines.len() < mines_count {
-                    let x = rand::thread_rng().gen_range(0..width);
-                    let y = rand::thread_rng().gen_range(0..height);
-                    mines.insert((x, y));
+            theme: Theme::default(),
+            feedback_events: Vec::new(),
+            moves: 0,
+            opened_at: Shared::default(),
+            blind_window: None,
+            flag_mode: false,
+            deductions: 0,
+            guesses: 0,
+            probes_left: 0,
+            probes_used: 0,
+            regions: Vec::new(),
+            unlocked_regions: 0,
+            commitment_nonce: None,
+            safe_first_click: false,
+            history: Shared::default(),
+            annotations: HashMap::default(),
+            seed: None,
+            flag_cap: false,
+            confirmation_policy: ConfirmationPolicy::Off,
+            pending_confirmation: None,
+            tokens_issued: 0,
+            timer: Timer::default(),
+            mines: mines.into(),
+        }
+    }
+
+    // Create an independent copy of the game where hypothetical moves can be applied
+    // and inspected without touching the current state. The sandbox starts from a
+    // snapshot, so it shares the board data with the game until a move changes it.
+    // Undo in the sandbox only reaches back to its own moves
+    pub fn sandbox(&self) -> Self {
+        self.snapshot().sandbox()
+    }
+
+    // Games created with an explicit layout or generator keep their layout exactly, so
+    // the safe first click is only on by default for random layouts
+    pub fn set_safe_first_click(&mut self, safe_first_click: bool) {
+        self.safe_first_click = safe_first_click;
+    }
+
+    pub fn safe_first_click(&self) -> bool {
+        self.safe_first_click
+    }
+
+    pub fn set_flag_cap(&mut self, flag_cap: bool) {
+        self.flag_cap = flag_cap;
+    }
+
+    pub fn flag_cap(&self) -> bool {
+        self.flag_cap
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn cell_state(&self, position: Position) -> CellState {
+        if !self.game_over {
+            if self.open_positions.contains(&position) {
+                if self.is_masked(position) {
+                    CellState::Masked
+                } else {
+                    CellState::Open(self.mines_around(position))
                 }
-                mines
-            },
+            } else if self.flagged_positions.contains(&position)
+                || (self.all_safe_cells_open() && self.mines.contains(&position))
+            {
+                // A won game shows every mine flagged
+                CellState::Flagged
+            } else if self.questioned_positions.contains(&position) {
+                CellState::Questioned
+            } else {
+                CellState::Hidden
+            }
+        } else if self.mines.contains(&position) {
+            // If the game is over, show the mines
+            if self.open_positions.contains(&position) {
+                CellState::Exploded
+            } else {
+                CellState::Mine
+            }
+        } else {
+            // If the position doesn't contain a mine, show the number of mines around it
+            CellState::Open(self.mines_around(position))
+        }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if self.game_over {
+            GameStatus::Lost
+        } else if self.all_safe_cells_open() {
+            GameStatus::Won
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    // Enable blind mode with the number of moves for which opened numbers stay visible
+    // or disable it with None
+    pub fn set_blind_mode(&mut self, window: Option<u32>) {
+        self.blind_window = window;
+    }
+
+    pub fn blind_mode(&self) -> Option<u32> {
+        self.blind_window
+    }
+
+    fn is_masked(&self, position: Position) -> bool {
+        match (self.blind_window, self.opened_at.get(&position)) {
+            (Some(window), Some(&(opened_at, _))) => {
+                self.moves.saturating_sub(opened_at) > window.saturating_add(1)
+            }
+            _ => false,
         }
     }
 
+    // The order in which the position was opened, starting from 0. Cells of a cascade get
+    // consecutive ordinals in the order the cascade reached them
+    pub fn open_ordinal(&self, position: Position) -> Option<u32> {
+        self.opened_at.get(&position).map(|&(_, ordinal)| ordinal)
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
     pub fn open(&mut self, pos: Position) -> &mut Self {
-        if let Some(result) = self.open_position(pos) {
-            match result {
-                OpeningResult::Mine => {
-                    self.open_positions.insert(pos);
-                    self.game_over = true;
-                    self
-                }
-                OpeningResult::NoMine(mines_around) => {
-                    // If the position doesn't have mines around, open the positions around it
-                    match mines_around {
-                        0 => {
-                            self.open_positions.insert(pos);
-                            // Recursively open the positions around the current one except the flagged ones and the already open ones
-                            self.neighbours(pos).iter().for_each(|position| {
-                                if self.can_be_opened(position) {
-                                    self.open(*position);
-                                }
-                            });
-                            self
-                        }
-                        _ => {
-                            self.open_positions.insert(pos);
-                            self
-                        }
+        self.record(Action::Open(pos), |minesweeper| {
+            minesweeper.open_judged(pos)
+        });
+        self
+    }
+
+    // Open a position and count it as a deduction or a guess
+    fn open_judged(&mut self, pos: Position) {
+        if self.can_be_opened(&pos) {
+            if self.safe_first_click && self.layout_is_movable() {
+                self.clear_first_click(pos);
+            }
+            // Judge the click before it changes the board
+            let proven_safe = self.provably_safe_cells(&[pos]);
+            self.judge(pos, &proven_safe);
+            self.open_unjudged(pos);
+        }
+    }
+
+    // Open a position even if it is flagged, clearing the flag first. For assist tools and
+    // confirmation flows where the player insists on opening a flagged cell
+    pub fn force_open(&mut self, position: Position) -> &mut Self {
+        self.record(Action::ForceOpen(position), |minesweeper| {
+            if minesweeper.status() == GameStatus::InProgress
+                && minesweeper.is_on_board(position)
+                && !minesweeper.is_locked(position)
+            {
+                minesweeper.flagged_positions.remove(&position);
+            }
+            minesweeper.open_judged(position);
+        });
+        self
+    }
+
+    // Open every unflagged neighbour of an open number whose flags around it match the
+    // number, as a single move. Wrong flags make the chord open a mine and lose the game
+    pub fn chord(&mut self, pos: Position) -> &mut Self {
+        let targets = self.chord_targets(pos);
+        if targets.is_empty() {
+            return self;
+        }
+
+        self.record(Action::Chord(pos), |minesweeper| {
+            // Judge every cell before the chord changes the board
+            let proven_safe = minesweeper.provably_safe_cells(&targets);
+            targets
+                .iter()
+                .for_each(|&target| minesweeper.judge(target, &proven_safe));
+
+            let open_before = minesweeper.open_positions.len();
+            let mut exploded = None;
+            for target in targets {
+                if minesweeper.can_be_opened(&target) {
+                    minesweeper.open_cascade(target);
+                    if minesweeper.game_over {
+                        exploded = Some(target);
                     }
                 }
             }
+            let opened_cells = minesweeper.open_positions.len() - open_before;
+            minesweeper.finish_open(exploded.unwrap_or(pos), opened_cells);
+        });
+        self
+    }
+
+    // The cells a chord on the position would open. Empty if the position isn't a visible
+    // number or the flags around it don't match the number
+    pub(crate) fn chord_targets(&self, pos: Position) -> Vec<Position> {
+        if self.status() != GameStatus::InProgress || !self.open_positions.contains(&pos) {
+            return Vec::new();
+        }
+        let CellState::Open(mines_around) = self.cell_state(pos) else {
+            return Vec::new();
+        };
+        let flags = self
+            .neighbours(pos)
+            .filter(|position| self.flagged_positions.contains(position))
+            .count();
+        if mines_around == 0 || flags != mines_around as usize {
+            return Vec::new();
+        }
+        self.neighbours(pos)
+            .filter(|position| self.can_be_opened(position))
+            .collect()
+    }
+
+    // The layout may only change before anything depends on it: before the first open,
+    // before a probe revealed a cell and before the layout was committed to
+    pub(crate) fn layout_is_movable(&self) -> bool {
+        self.opened_at.is_empty() && self.probes_used == 0 && self.commitment_nonce.is_none()
+    }
+
+    // Move the mines out of the clicked cell and its neighbours to random free cells, so
+    // the first click opens an area. If the board is too crowded for that, only the
+    // clicked cell is cleared
+    pub(crate) fn clear_first_click(&mut self, pos: Position) {
+        let mut zone: Vec<Position> = std::iter::once(pos).chain(self.neighbours(pos)).collect();
+        let free_outside = |minesweeper: &Self, zone: &[Position]| -> Vec<Position> {
+            (0..minesweeper.height)
+                .flat_map(|y| (0..minesweeper.width).map(move |x| (x, y)))
+                .filter(|position| {
+                    !zone.contains(position) && !minesweeper.mines.contains(position)
+                })
+                .collect()
+        };
+
+        let mut free = free_outside(self, &zone);
+        let zone_mines = zone
+            .iter()
+            .filter(|position| self.mines.contains(position))
+            .count();
+        if free.len() < zone_mines {
+            zone = vec![pos];
+            free = free_outside(self, &zone);
+        }
+
+        match self.seed {
+            Some(seed) => self.relocate_mines(zone, free, &mut StdRng::seed_from_u64(seed)),
+            None => self.relocate_mines(zone, free, &mut rand::thread_rng()),
+        }
+    }
+
+    fn relocate_mines(&mut self, zone: Vec<Position>, mut free: Vec<Position>, rng: &mut impl Rng) {
+        for position in zone {
+            if !free.is_empty() && self.mines.remove(&position) {
+                let index = rng.gen_range(0..free.len());
+                self.mines.insert(free.swap_remove(index));
+            }
+        }
+    }
+
+    // Count opening the position as a deduction if the numbers proved it safe before the
+    // move, otherwise as a guess
+    pub(crate) fn judge(&mut self, pos: Position, proven_safe: &[Position]) {
+        if proven_safe.contains(&pos) {
+            self.deductions += 1;
         } else {
-            // If the position is already open or flagged, do nothing
-            self
+            self.guesses += 1;
+        }
+    }
+
+    // Open a position which can be opened as one move, without counting it as a deduction
+    // or a guess
+    pub(crate) fn open_unjudged(&mut self, pos: Position) {
+        let open_before = self.open_positions.len();
+        self.open_cascade(pos);
+        let opened_cells = self.open_positions.len() - open_before;
+        self.finish_open(pos, opened_cells);
+    }
+
+    // Count the move which opened `opened_cells` cells around the position and report it
+    pub(crate) fn finish_open(&mut self, pos: Position, opened_cells: usize) {
+        self.moves += 1;
+        self.update_unlocked_regions();
+        let events = FeedbackEvent::after_open(self, pos, opened_cells);
+        self.feedback_events.extend(events);
+        self.debug_audit();
+    }
+
+    // Drain the feedback events collected since the last call
+    pub fn take_feedback_events(&mut self) -> Vec<FeedbackEvent> {
+        std::mem::take(&mut self.feedback_events)
+    }
+
+    // Open the position and, while the opened cells have no mines around, the cells
+    // around them. The cells to open wait on a stack instead of the call stack, so a
+    // large empty board can't overflow it
+    pub(crate) fn open_cascade(&mut self, pos: Position) -> &mut Self {
+        let mut pending = vec![pos];
+        while let Some(position) = pending.pop() {
+            // Cells which are already open or flagged, e.g. reached twice, are skipped
+            match self.open_position(position) {
+                Some(OpeningResult::Mine) => self.game_over = true,
+                Some(OpeningResult::NoMine(0)) => {
+                    // Pushed in reverse, so the cells open in the order of `neighbours`
+                    let start = pending.len();
+                    pending.extend(self.neighbours(position));
+                    pending[start..].reverse();
+                }
+                Some(OpeningResult::NoMine(_)) | None => {}
+            }
         }
+        self
     }
 
     pub fn mines_around(&self, pos: Position) -> u8 {
         // Safely iterate over the 3x3 grid around the position and count the mines
         self.neighbours(pos)
-            .iter()
-            .filter(|&position| self.mines.contains(position))
+            .filter(|position| self.mines.contains(position))
             .count() as u8
     }
 
@@ -104,8 +539,11 @@ impl Minesweeper {
             return None;
         }
 
-        // Insert the position in the open fields
+        // Insert the position in the open fields. An open cell needs no question mark
         self.open_positions.insert(position);
+        self.questioned_positions.remove(&position);
+        let ordinal = self.opened_at.len() as u32;
+        self.opened_at.insert(position, (self.moves, ordinal));
 
         // Check if the position contains a mine
         if self.mines.contains(&position) {
@@ -118,28 +556,123 @@ impl Minesweeper {
         Some(OpeningResult::NoMine(mines_around))
     }
 
-    fn neighbours(&self, (x, y): Position) -> HashSet<Position> {
-        // Safely iterate over the 3x3 grid around the position and get neighbours' positions
+    pub(crate) fn neighbours(&self, (x, y): Position) -> impl Iterator<Item = Position> {
+        // Safely iterate over the 3x3 grid around the position and get neighbours' positions.
+        // The ranges never repeat a position, and copying the size keeps the iterator from
+        // borrowing the game, so the flood fill can open cells while iterating
+        let (width, height) = (self.width, self.height);
         (x.saturating_sub(1)..=x.saturating_add(1))
             .flat_map(move |i| (y.saturating_sub(1)..=y.saturating_add(1)).map(move |j| (i, j)))
-            .filter(move |&(i, j)| (i, j) != (x, y) && i < self.width && j < self.height)
-            .collect() // Collect the positions in a HashSet to avoid duplicates
+            .filter(move |&(i, j)| (i, j) != (x, y) && i < width && j < height)
     }
 
-    pub fn toggle_flag(&mut self, position: Position) {
-        if !self.game_over {
-            if self.flagged_positions.contains(&position) {
-                self.flagged_positions.remove(&position);
+    #[cfg(test)]
+    fn neighbour_set(&self, position: Position) -> HashSet<Position> {
+        self.neighbours(position).collect()
+    }
+
+    // Toggle the flag of a hidden position. Return None if the position can't be flagged
+    // because the game is over, the position is open or it is outside the board, or if
+    // the flag cap is on and every mine already has a flag
+    pub fn toggle_flag(&mut self, position: Position) -> Option<FlagToggle> {
+        if !self.can_be_marked(position)
+            || (!self.flagged_positions.contains(&position) && !self.has_flags_left())
+        {
+            return None;
+        }
+
+        let mark = self.record(Action::ToggleFlag(position), |minesweeper| {
+            let mark = if minesweeper.flagged_positions.remove(&position) {
+                CellMark::Cleared
             } else {
-                self.flagged_positions.insert(position);
-            }
+                minesweeper.questioned_positions.remove(&position);
+                minesweeper.flagged_positions.insert(position);
+                CellMark::Flagged
+            };
+            minesweeper.moves += 1;
+            minesweeper.debug_audit();
+            mark
+        });
+
+        Some(FlagToggle {
+            mark,
+            remaining_mines: self.remaining_mines(),
+        })
+    }
+
+    // Cycle the mark of a hidden position like the classic game does: no mark, a flag, a
+    // question mark and no mark again. A question mark only reminds the player of a cell
+    // they are unsure about, the cell can still be opened. With the flag cap on and no
+    // flags left the flag is skipped. Return None if the position can't be marked, see
+    // `toggle_flag`
+    pub fn cycle_mark(&mut self, position: Position) -> Option<FlagToggle> {
+        if !self.can_be_marked(position) {
+            return None;
         }
+
+        let mark = self.record(Action::CycleMark(position), |minesweeper| {
+            let mark = if minesweeper.flagged_positions.remove(&position) {
+                minesweeper.questioned_positions.insert(position);
+                CellMark::Questioned
+            } else if minesweeper.questioned_positions.remove(&position) {
+                CellMark::Cleared
+            } else if minesweeper.has_flags_left() {
+                minesweeper.flagged_positions.insert(position);
+                CellMark::Flagged
+            } else {
+                minesweeper.questioned_positions.insert(position);
+                CellMark::Questioned
+            };
+            minesweeper.moves += 1;
+            minesweeper.debug_audit();
+            mark
+        });
+
+        Some(FlagToggle {
+            mark,
+            remaining_mines: self.remaining_mines(),
+        })
+    }
+
+    pub(crate) fn can_be_marked(&self, position: Position) -> bool {
+        self.status() == GameStatus::InProgress
+            && !self.open_positions.contains(&position)
+            && self.is_on_board(position)
+            && !self.is_locked(position)
+    }
+
+    pub fn remaining_mines(&self) -> i32 {
+        self.mines.len() as i32 - self.flagged_positions.len() as i32
+    }
+
+    pub(crate) fn has_flags_left(&self) -> bool {
+        !self.flag_cap || self.remaining_mines() > 0
+    }
+
+    pub(crate) fn debug_audit(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(violations) = self.audit() {
+            panic!("Invariants violated: {violations:?}");
+        }
+    }
+
+    fn is_on_board(&self, (x, y): Position) -> bool {
+        x < self.width && y < self.height
     }
 
-    fn can_be_opened(&self, position: &Position) -> bool {
-        !self.open_positions.contains(position)
+    pub(crate) fn all_safe_cells_open(&self) -> bool {
+        self.open_positions.len() + self.mines.len() == self.width as usize * self.height as usize
+    }
+
+    // Cells outside the board must never be opened, the win is detected by the number of
+    // open cells. Question marks don't protect a cell like flags do
+    pub(crate) fn can_be_opened(&self, position: &Position) -> bool {
+        self.is_on_board(*position)
+            && !self.all_safe_cells_open()
+            && !self.open_positions.contains(position)
             && !self.flagged_positions.contains(position)
             && !self.game_over
+            && !self.is_locked(*position)
     }
 }
 
@@ -149,35 +682,8 @@ impl Display for Minesweeper {
         for y in 0..self.height {
             // Iterate over the columns
             for x in 0..self.width {
-                let position = (x, y);
-
-                if !self.game_over {
-                    // Check if the position is open
-                    if self.open_positions.contains(&position) {
-                        // If the position doesn't contain a mine, add the number of mines around it
-                        let mines_around = self.mines_around(position);
-                        // We can't have more than 8 mines around a position
-                        f.write_fmt(format_args!("{} ", mines_around))?;
-                    } else if self.flagged_positions.contains(&position) {
-                        // If the position is flagged, add a flag to the board
-                        f.write_str(&format!("{} ", FLAG))?;
-                    } else {
-                        f.write_str(&format!("{} ", CELL))?;
-                    }
-                } else {
-                    // If the game is over, show the mines
-                    if self.mines.contains(&position) {
-                        if self.open_positions.contains(&position) {
-                            f.write_str(&format!("{} ", EXPLOSION))?;
-                        } else {
-                            f.write_str(&format!("{} ", MINE))?;
-                        }
-                    } else {
-                        // If the position doesn't contain a mine, show the number of mines around it
-                        let mines_around = self.mines_around(position);
-                        f.write_fmt(format_args!("{} ", mines_around))?;
-                    }
-                }
+                let cell_state = self.cell_state((x, y));
+                f.write_fmt(format_args!("{} ", self.theme.glyph(cell_state)))?;
             }
             // Add a newline character to the board to separate the rows
             // We don't add a newline character after the last row
@@ -192,14 +698,15 @@ impl Display for Minesweeper {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::{ThemePreset, CELL, EXPLOSION, FLAG, MINE};
 
     fn insert_mines_around_neighbours(minesweeper: &mut Minesweeper, cell: Position) {
         // Get cell's neighbours
-        let cell_neighbours = minesweeper.neighbours(cell);
+        let cell_neighbours = minesweeper.neighbour_set(cell);
 
         // Insert mines around cell's neighbours
         cell_neighbours.iter().for_each(|pos| {
-            minesweeper.neighbours(*pos).iter().for_each(|position| {
+            minesweeper.neighbour_set(*pos).iter().for_each(|position| {
                 if !cell_neighbours.contains(position) && *position != cell {
                     minesweeper.mines.insert(*position);
                 }
@@ -239,6 +746,34 @@ mod tests {
         Minesweeper::new(10, 10, 100);
     }
 
+    #[test]
+    fn test_are_valid_parameters() {
+        assert!(Minesweeper::are_valid_parameters(30, 16, 99), "Expert");
+        assert!(!Minesweeper::are_valid_parameters(3, 3, 9), "No safe cell");
+        assert!(
+            Minesweeper::are_valid_parameters(300, 300, 100),
+            "Area doesn't fit in u16"
+        );
+        assert!(
+            !Minesweeper::are_valid_parameters(65_535, 65_535, 10),
+            "Larger than the largest board"
+        );
+        assert!(Minesweeper::are_valid_parameters(
+            MAX_BOARD_SIDE,
+            MAX_BOARD_SIDE,
+            10
+        ));
+    }
+
+    #[test]
+    fn test_open_large_empty_board() {
+        // ================================================
+        // Test that a cascade over tens of thousands of cells doesn't overflow the stack
+        let mut minesweeper = Minesweeper::from_mines(MAX_BOARD_SIDE, 64, [(0, 0)]);
+        minesweeper.open((MAX_BOARD_SIDE - 1, 63));
+        assert_eq!(minesweeper.status(), GameStatus::Won);
+    }
+
     #[test]
     fn test_new() {
         // ================================================
@@ -360,7 +895,7 @@ mod tests {
         // ================================================
         // Test the case when the cell is in the upper left corner
         let cell = (0, 0);
-        let neighbors = minesweeper.neighbours(cell);
+        let neighbors = minesweeper.neighbour_set(cell);
         assert_eq!(neighbors.len(), 3);
         assert!(neighbors.contains(&(0, 1)));
         assert!(neighbors.contains(&(1, 0)));
@@ -369,7 +904,7 @@ mod tests {
         // ================================================
         // Test the case when the cell is in the lower right corner
         let cell = (9, 9);
-        let neighbors = minesweeper.neighbours(cell);
+        let neighbors = minesweeper.neighbour_set(cell);
         assert_eq!(neighbors.len(), 3);
         assert!(neighbors.contains(&(8, 8)));
         assert!(neighbors.contains(&(8, 9)));
@@ -378,7 +913,7 @@ mod tests {
         // ================================================
         // Test the case when the cell is near the border
         let cell = (0, 5);
-        let neighbors = minesweeper.neighbours(cell);
+        let neighbors = minesweeper.neighbour_set(cell);
         assert_eq!(neighbors.len(), 5);
         assert!(neighbors.contains(&(0, 4)));
         assert!(neighbors.contains(&(0, 6)));
@@ -389,7 +924,7 @@ mod tests {
         // ================================================
         // Test the case when the cell is in the middle
         let cell = (5, 5);
-        let neighbors = minesweeper.neighbours(cell);
+        let neighbors = minesweeper.neighbour_set(cell);
         assert_eq!(neighbors.len(), 8);
         assert!(neighbors.contains(&(4, 4)));
         assert!(neighbors.contains(&(4, 5)));
@@ -399,6 +934,18 @@ mod tests {
         assert!(neighbors.contains(&(6, 4)));
         assert!(neighbors.contains(&(6, 5)));
         assert!(neighbors.contains(&(6, 6)));
+
+        // ================================================
+        // Test that the iterator yields every neighbour once
+        [(0, 0), (9, 9), (0, 5), (5, 5)]
+            .into_iter()
+            .for_each(|cell| {
+                assert_eq!(
+                    minesweeper.neighbours(cell).count(),
+                    minesweeper.neighbour_set(cell).len(),
+                    "No duplicates around {cell:?}"
+                );
+            });
     }
 
     #[test]
@@ -406,6 +953,7 @@ mod tests {
         // ================================================
         // Test the case when the cell has a mine
         let mut minesweeper = Minesweeper::new(10, 10, 10);
+        minesweeper.set_safe_first_click(false);
         let cell = (0, 0);
         // Insert mine in the cell
         minesweeper.mines.insert(cell);
@@ -421,6 +969,7 @@ mod tests {
         let cell = (0, 0);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         assert!(minesweeper.mines.is_empty(), "No mines");
         assert_eq!(minesweeper.open_positions.len(), 0, "No open positions");
 
@@ -449,6 +998,7 @@ mod tests {
         let cell = (9, 0);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         assert!(minesweeper.mines.is_empty(), "No mines");
         assert_eq!(minesweeper.open_positions.len(), 0, "No open positions");
 
@@ -477,6 +1027,7 @@ mod tests {
         let cell = (5, 5);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         assert!(minesweeper.mines.is_empty(), "No mines");
         assert_eq!(minesweeper.open_positions.len(), 0, "No open positions");
 
@@ -505,6 +1056,7 @@ mod tests {
         let cell = (0, 5);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         assert!(minesweeper.mines.is_empty(), "No mines");
         assert_eq!(minesweeper.open_positions.len(), 0, "No open positions");
 
@@ -528,6 +1080,7 @@ mod tests {
         // ================================================
         // Test the case when the cell has a mine next to it
         let mut minesweeper = Minesweeper::new(10, 10, 10);
+        minesweeper.set_safe_first_click(false);
         let cell = (5, 5);
         // Insert 1 mine around the cell
         minesweeper.mines.insert((5, 6));
@@ -563,6 +1116,7 @@ mod tests {
         let cell = (5, 5);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         // No cell is opened
         assert_eq!(minesweeper.open_positions.len(), 0, "No cell is opened");
         // Insert 1 mine around the cell to prevent opening other cells
@@ -588,18 +1142,195 @@ mod tests {
             0,
             "No position flagged"
         );
-        minesweeper.toggle_flag(cell);
+        assert_eq!(
+            minesweeper.toggle_flag(cell),
+            Some(FlagToggle {
+                mark: CellMark::Flagged,
+                remaining_mines: 9
+            }),
+            "Cell flagged, 9 mines remain"
+        );
         assert_eq!(minesweeper.flagged_positions.len(), 1, "1 position flagged");
         assert!(
             minesweeper.flagged_positions.contains(&cell),
             "Tested cell flagged"
         );
-        minesweeper.toggle_flag(cell);
+        assert_eq!(
+            minesweeper.toggle_flag(cell),
+            Some(FlagToggle {
+                mark: CellMark::Cleared,
+                remaining_mines: 10
+            }),
+            "Flag cleared, 10 mines remain"
+        );
         assert_eq!(
             minesweeper.flagged_positions.len(),
             0,
             "1 flagged position removed"
         );
+
+        // ================================================
+        // Test the case when the cell is open
+        minesweeper.mines.remove(&cell);
+        minesweeper.open_positions.insert(cell);
+        assert_eq!(
+            minesweeper.toggle_flag(cell),
+            None,
+            "Open cell can't be flagged"
+        );
+        assert!(
+            minesweeper.flagged_positions.is_empty(),
+            "No position flagged"
+        );
+
+        // ================================================
+        // Test the case when the cell is outside the board
+        assert_eq!(
+            minesweeper.toggle_flag((10, 0)),
+            None,
+            "Cell outside the board"
+        );
+
+        // ================================================
+        // Test the case when the game is over
+        minesweeper.game_over = true;
+        assert_eq!(minesweeper.toggle_flag((0, 0)), None, "Game is over");
+    }
+
+    #[test]
+    fn test_cycle_mark() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_theme(Theme::ASCII);
+
+        // ================================================
+        // Test the cycle of no mark, flag, question mark and no mark
+        let marks: Vec<CellMark> = (0..3)
+            .filter_map(|_| minesweeper.cycle_mark((0, 0)))
+            .map(|toggle| toggle.mark)
+            .collect();
+        assert_eq!(
+            marks,
+            vec![CellMark::Flagged, CellMark::Questioned, CellMark::Cleared]
+        );
+        assert_eq!(minesweeper.moves(), 3, "Every step is a move");
+        minesweeper.cycle_mark((1, 0));
+        minesweeper.cycle_mark((1, 0));
+        assert_eq!(minesweeper.cell_state((1, 0)), CellState::Questioned);
+        assert_eq!(minesweeper.remaining_mines(), 2, "Question isn't a flag");
+        assert_eq!(
+            minesweeper.to_string().lines().next(),
+            Some("# ? # "),
+            "Question rendered"
+        );
+
+        // ================================================
+        // Test that a flag replaces the question mark
+        minesweeper.toggle_flag((1, 0));
+        assert_eq!(minesweeper.cell_state((1, 0)), CellState::Flagged);
+        minesweeper.toggle_flag((1, 0));
+        assert_eq!(minesweeper.cell_state((1, 0)), CellState::Hidden);
+
+        // ================================================
+        // Test that the flag is skipped when no flags are left
+        minesweeper.set_flag_cap(true);
+        minesweeper.toggle_flag((2, 2));
+        minesweeper.toggle_flag((0, 0));
+        assert_eq!(
+            minesweeper.cycle_mark((1, 0)).map(|toggle| toggle.mark),
+            Some(CellMark::Questioned)
+        );
+
+        // ================================================
+        // Test that a question mark can be opened, unlike a flag
+        assert!(minesweeper.can_be_opened(&(1, 0)));
+        minesweeper.open((1, 0));
+        assert_eq!(minesweeper.cell_state((1, 0)), CellState::Open(1));
+        assert!(minesweeper.questioned_positions.is_empty(), "Mark removed");
+        minesweeper.undo();
+        assert_eq!(
+            minesweeper.cell_state((1, 0)),
+            CellState::Questioned,
+            "Undo restores the mark"
+        );
+        assert_eq!(minesweeper.cycle_mark((4, 0)), None, "Outside the board");
+    }
+
+    #[test]
+    fn test_flag_cap() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.toggle_flag((0, 1));
+        minesweeper.toggle_flag((1, 1));
+        minesweeper.toggle_flag((2, 1));
+        assert_eq!(minesweeper.remaining_mines(), -1, "Uncapped by default");
+
+        // ================================================
+        // Test that no flag is placed beyond the mine count
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_flag_cap(true);
+        minesweeper.toggle_flag((0, 1));
+        minesweeper.toggle_flag((1, 1));
+        assert_eq!(minesweeper.toggle_flag((2, 1)), None, "Out of flags");
+        assert!(
+            !minesweeper
+                .legal_actions()
+                .contains(&Action::ToggleFlag((2, 1))),
+            "Not legal either"
+        );
+        assert_eq!(minesweeper.remaining_mines(), 0);
+
+        // ================================================
+        // Test that flags can still be cleared and moved
+        assert!(minesweeper.toggle_flag((1, 1)).is_some(), "Flag cleared");
+        assert!(minesweeper.toggle_flag((2, 1)).is_some(), "Flag moved");
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        let cell = (5, 5);
+        // Remove all mines
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Insert 1 mine around the cell to prevent opening other cells
+        minesweeper.mines.insert((5, 6));
+
+        // ================================================
+        // Test that moves applied to the sandbox don't affect the game
+        let mut sandbox = minesweeper.sandbox();
+        sandbox.open(cell);
+        sandbox.toggle_flag((5, 6));
+        assert_eq!(
+            sandbox.open_positions.len(),
+            1,
+            "1 cell is opened in the sandbox"
+        );
+        assert_eq!(
+            sandbox.flagged_positions.len(),
+            1,
+            "1 cell is flagged in the sandbox"
+        );
+        assert!(
+            minesweeper.open_positions.is_empty(),
+            "No cell is opened in the game"
+        );
+        assert!(
+            minesweeper.flagged_positions.is_empty(),
+            "No cell is flagged in the game"
+        );
+
+        // ================================================
+        // Test that the sandbox starts from the current state of the game
+        minesweeper.open(cell);
+        let mut sandbox = minesweeper.sandbox();
+        assert_eq!(sandbox.to_string(), minesweeper.to_string(), "Same board");
+        assert!(
+            sandbox.open_positions.ptr_eq(&minesweeper.open_positions),
+            "Board data shared until a move"
+        );
+        assert!(!sandbox.can_undo(), "Game moves can't be undone");
+        sandbox.open((5, 6));
+        assert!(sandbox.game_over, "Mine opened in the sandbox, game over");
+        assert!(!minesweeper.game_over, "The game is not over");
     }
 
     #[test]
@@ -627,6 +1358,7 @@ mod tests {
         let cell = (5, 5);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         // Insert mines next to the cell
         minesweeper.mines.insert((5, 4));
         // Open the cell
@@ -668,6 +1400,7 @@ mod tests {
         let cell = (0, 0);
         // Remove all mines
         minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
         // Add a mine to the cell
         minesweeper.mines.insert(cell);
         // Add mines around the cell's neighbors
@@ -689,10 +1422,367 @@ mod tests {
             expected_line.as_str(),
             "Line 2 has 3 mines in columns 0, 1, 2"
         );
-        assert_eq!(
-            string_lines[0].chars().next(),
-            Some(EXPLOSION),
+        assert!(
+            string_lines[0].starts_with(EXPLOSION),
             "Cell (0, 0) has an explosion"
         );
     }
+
+    #[test]
+    fn test_to_string_with_theme() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        let cell = (5, 5);
+        // Remove all mines
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Insert mines next to the cell
+        minesweeper.mines.insert((5, 4));
+        minesweeper.mines.insert((4, 4));
+        // Open the cell and flag one of the mines
+        minesweeper.open(cell);
+        minesweeper.toggle_flag((4, 4));
+
+        // ================================================
+        // Test the keycap theme
+        minesweeper.set_theme(ThemePreset::Keycap.theme());
+        let minesweeper_str = minesweeper.to_string();
+        let string_lines: Vec<&str> = minesweeper_str.lines().collect();
+        let expected_line =
+            format!("{CELL} {CELL} {CELL} {CELL} {CELL} 2️⃣ {CELL} {CELL} {CELL} {CELL} ");
+        assert_eq!(
+            string_lines[5],
+            expected_line.as_str(),
+            "Cell (5, 5) is opened and has a 2 keycap"
+        );
+
+        // ================================================
+        // Test the letters theme
+        minesweeper.set_theme(ThemePreset::Letters.theme());
+        let minesweeper_str = minesweeper.to_string();
+        let string_lines: Vec<&str> = minesweeper_str.lines().collect();
+        assert_eq!(
+            string_lines[4], "# # # # ! # # # # # ",
+            "Cell (4, 4) is flagged"
+        );
+        assert_eq!(
+            string_lines[5], "# # # # # B # # # # ",
+            "Cell (5, 5) has 2 mines around"
+        );
+
+        // ================================================
+        // Test that the theme is used when the game is over
+        minesweeper.open((5, 4));
+        let minesweeper_str = minesweeper.to_string();
+        let string_lines: Vec<&str> = minesweeper_str.lines().collect();
+        assert_eq!(
+            string_lines[4], ". . . A * X A . . . ",
+            "Mine (5, 4) exploded"
+        );
+    }
+
+    #[test]
+    fn test_to_string_monospace() {
+        // Seeded, so opening the first row can't cascade over the whole board and win
+        let mut minesweeper = Minesweeper::new_with_seed(10, 10, 10, 1);
+        minesweeper.set_theme(ThemePreset::Ascii.theme());
+
+        // Check that every line has exactly 10 cells of the same width
+        let assert_fixed_width = |minesweeper: &Minesweeper| {
+            let minesweeper_str = minesweeper.to_string();
+            let string_lines: Vec<&str> = minesweeper_str.lines().collect();
+            assert_eq!(string_lines.len(), 10, "The board has 10 lines");
+            string_lines.into_iter().for_each(|line| {
+                assert!(line.is_ascii(), "Line has ASCII characters only");
+                assert_eq!(line.len(), 20, "Each cell takes 2 columns");
+                line.chars().skip(1).step_by(2).for_each(|c| {
+                    assert_eq!(c, ' ', "Cells are separated by a single space");
+                });
+            });
+        };
+
+        // ================================================
+        // Test a new game
+        assert_fixed_width(&minesweeper);
+
+        // ================================================
+        // Test a game with open and flagged cells
+        let mine = minesweeper.mines.iter().next().unwrap();
+        minesweeper.toggle_flag(mine);
+        (0..10).for_each(|x| {
+            if !minesweeper.mines.contains(&(x, 0)) {
+                minesweeper.open((x, 0));
+            }
+        });
+        assert_fixed_width(&minesweeper);
+
+        // ================================================
+        // Test a lost game
+        minesweeper.toggle_flag(mine);
+        minesweeper.open(mine);
+        assert!(minesweeper.game_over, "Mine opened, game over");
+        assert_fixed_width(&minesweeper);
+    }
+
+    #[test]
+    fn test_blind_mode() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        // Remove all mines
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Insert mines next to the cells (5, 5) and (7, 7)
+        minesweeper.mines.insert((5, 6));
+        minesweeper.mines.insert((7, 8));
+        minesweeper.set_blind_mode(Some(1));
+
+        // ================================================
+        // Test that the number is visible right after opening and during the window
+        minesweeper.open((5, 5));
+        assert_eq!(minesweeper.moves(), 1, "1 move made");
+        assert_eq!(minesweeper.cell_state((5, 5)), CellState::Open(1));
+        minesweeper.toggle_flag((0, 0));
+        assert_eq!(minesweeper.cell_state((5, 5)), CellState::Open(1));
+
+        // ================================================
+        // Test that the number is masked once the window is over
+        minesweeper.open((7, 7));
+        assert_eq!(minesweeper.cell_state((5, 5)), CellState::Masked);
+        assert_eq!(minesweeper.cell_state((7, 7)), CellState::Open(1));
+        let minesweeper_str = minesweeper.to_string();
+        let string_lines: Vec<&str> = minesweeper_str.lines().collect();
+        let expected_line =
+            format!("{CELL} {CELL} {CELL} {CELL} {CELL} 0 {CELL} {CELL} {CELL} {CELL} ");
+        assert_eq!(
+            string_lines[5],
+            expected_line.as_str(),
+            "Cell (5, 5) is rendered as a blank open cell"
+        );
+
+        // ================================================
+        // Test that disabling blind mode shows the numbers again
+        minesweeper.set_blind_mode(None);
+        assert_eq!(minesweeper.cell_state((5, 5)), CellState::Open(1));
+        minesweeper.set_blind_mode(Some(u32::MAX));
+        assert_eq!(
+            minesweeper.cell_state((5, 5)),
+            CellState::Open(1),
+            "Largest window"
+        );
+
+        // ================================================
+        // Test that all numbers are revealed when the game is over
+        minesweeper.set_blind_mode(Some(0));
+        minesweeper.open((5, 6));
+        assert_eq!(minesweeper.cell_state((5, 5)), CellState::Open(1));
+    }
+
+    #[test]
+    fn test_from_mines() {
+        let minesweeper = Minesweeper::from_mines(4, 3, [(0, 0), (3, 2)]);
+        assert_eq!((minesweeper.width(), minesweeper.height()), (4, 3));
+        assert_eq!(minesweeper.mines.len(), 2, "2 mines");
+        assert!(minesweeper.mines.contains(&(0, 0)));
+        assert!(minesweeper.mines.contains(&(3, 2)));
+
+        // ================================================
+        // Test a board without mines
+        let minesweeper = Minesweeper::from_mines(2, 2, []);
+        assert!(minesweeper.mines.is_empty(), "No mines");
+    }
+
+    #[test]
+    #[should_panic = "Mine outside the board"]
+    fn test_from_mines_outside_board() {
+        Minesweeper::from_mines(4, 3, [(4, 0)]);
+    }
+
+    #[test]
+    fn test_new_with_seed() {
+        let mines = |minesweeper: &Minesweeper| {
+            let mut mines: Vec<Position> = minesweeper.mines.iter().collect();
+            mines.sort();
+            mines
+        };
+
+        // ================================================
+        // Test that a seed gives the same layout every time
+        let mut minesweeper = Minesweeper::new_with_seed(16, 16, 40, 7);
+        let other = Minesweeper::new_with_seed(16, 16, 40, 7);
+        assert_eq!(minesweeper.seed(), Some(7));
+        assert_eq!(mines(&minesweeper), mines(&other), "Same layout");
+        assert_ne!(
+            mines(&minesweeper),
+            mines(&Minesweeper::new_with_seed(16, 16, 40, 8)),
+            "Other seed"
+        );
+        assert_eq!(Minesweeper::new(16, 16, 40).seed(), None);
+
+        // ================================================
+        // Test that the first click moves mines the same way every time
+        let mut other = other;
+        let mine = minesweeper.mines.iter().next().unwrap();
+        minesweeper.open(mine);
+        other.open(mine);
+        assert_eq!(mines(&minesweeper), mines(&other), "Same relocation");
+        assert_eq!(minesweeper.status(), GameStatus::InProgress);
+    }
+
+    #[test]
+    fn test_chord() {
+        //     * 1 .
+        //     1 1 .
+        //     . . .
+        let mut minesweeper = crate::board!["*o.", "oo.", "..."];
+
+        // ================================================
+        // Test that chords need the flags to match the number
+        minesweeper.chord((1, 1));
+        assert_eq!(minesweeper.moves(), 0, "No flags around");
+        minesweeper.toggle_flag((0, 0));
+        minesweeper.chord((2, 2)).chord((0, 0));
+        assert_eq!(minesweeper.moves(), 1, "Only numbers can be chorded");
+
+        // ================================================
+        // Test that a chord opens every unflagged neighbour as one move
+        minesweeper.chord((1, 1));
+        assert_eq!(minesweeper.moves(), 2, "1 move");
+        assert_eq!(minesweeper.status(), GameStatus::Won);
+        assert_eq!(
+            minesweeper.luck_stats().deductions,
+            5,
+            "Every cell was proven"
+        );
+
+        // ================================================
+        // Test that wrong flags open a mine
+        let mut minesweeper = crate::board!["*o.", "oo.", "..."];
+        minesweeper.toggle_flag((2, 0));
+        minesweeper.chord((1, 0));
+        assert_eq!(minesweeper.status(), GameStatus::Lost);
+        assert_eq!(
+            minesweeper.take_feedback_events(),
+            vec![FeedbackEvent::Explosion { x: 0, y: 0 }],
+            "The exploded mine is reported"
+        );
+    }
+
+    #[test]
+    fn test_win() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_theme(Theme::ASCII);
+
+        // ================================================
+        // Test that cells outside the board don't count towards the win
+        minesweeper.open((5, 5)).open((3, 0));
+        assert_eq!(minesweeper.moves(), 0, "Nothing opened");
+        assert!(minesweeper.open_positions.is_empty());
+
+        // ================================================
+        // Test that the win shows every mine flagged
+        minesweeper.open((2, 0)).open((0, 2));
+        assert_eq!(minesweeper.status(), GameStatus::Won);
+        assert_eq!(minesweeper.to_string(), "F 1 . \n1 2 1 \n. 1 F \n");
+        assert_eq!(minesweeper.cell_state((0, 0)), CellState::Flagged);
+
+        // ================================================
+        // Test that a won game accepts no more actions
+        minesweeper.open((0, 0));
+        assert_eq!(
+            minesweeper.status(),
+            GameStatus::Won,
+            "Mine can't be opened"
+        );
+        assert_eq!(minesweeper.toggle_flag((2, 2)), None);
+        assert_eq!(minesweeper.moves(), 2);
+    }
+
+    #[test]
+    fn test_safe_first_click() {
+        // ================================================
+        // Test that the first click and its neighbours are cleared
+        for _ in 0..20 {
+            let mut minesweeper = Minesweeper::new(10, 10, 30);
+            assert!(minesweeper.safe_first_click(), "On for random layouts");
+            let mine = minesweeper.mines.iter().next().unwrap();
+            minesweeper.open(mine);
+            assert_eq!(minesweeper.status(), GameStatus::InProgress);
+            assert_eq!(minesweeper.mines.len(), 30, "Mines are moved, not removed");
+            assert_eq!(minesweeper.mines_around(mine), 0, "Neighbours are cleared");
+        }
+
+        // ================================================
+        // Test that only the clicked cell is cleared on a crowded board
+        let mut minesweeper = Minesweeper::new(3, 3, 8);
+        let mine = minesweeper.mines.iter().next().unwrap();
+        minesweeper.open(mine);
+        assert_eq!(minesweeper.status(), GameStatus::Won, "The only safe cell");
+
+        // ================================================
+        // Test that a committed layout and explicit layouts are final
+        let mut minesweeper = Minesweeper::new(10, 10, 30);
+        minesweeper.commit();
+        let mine = minesweeper.mines.iter().next().unwrap();
+        minesweeper.open(mine);
+        assert_eq!(minesweeper.status(), GameStatus::Lost, "Committed layout");
+
+        let mut minesweeper = crate::board!["*."];
+        assert!(!minesweeper.safe_first_click(), "Off for explicit layouts");
+        minesweeper.open((0, 0));
+        assert_eq!(minesweeper.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn test_open_ordinal() {
+        let mut minesweeper = crate::board!["*...", "....", "..*."];
+        assert_eq!(minesweeper.open_ordinal((3, 0)), None, "Not opened");
+
+        // ================================================
+        // Test that the clicked cell comes first and the cascade follows
+        minesweeper.open((3, 0));
+        let opened = minesweeper.open_positions.len() as u32;
+        assert_eq!(opened, 6, "Cascade stops at the numbers");
+        assert_eq!(minesweeper.open_ordinal((3, 0)), Some(0));
+        let mut ordinals: Vec<u32> = minesweeper
+            .open_positions
+            .iter()
+            .filter_map(|&position| minesweeper.open_ordinal(position))
+            .collect();
+        ordinals.sort();
+        assert_eq!(
+            ordinals,
+            (0..opened).collect::<Vec<_>>(),
+            "Distinct ordinals"
+        );
+
+        // ================================================
+        // Test that later clicks continue the order
+        minesweeper.open((0, 2));
+        assert_eq!(minesweeper.open_ordinal((0, 2)), Some(opened));
+    }
+
+    #[test]
+    fn test_force_open() {
+        let mut minesweeper = crate::board!["*f.", "...", "..*"];
+        minesweeper.open((1, 0));
+        assert!(
+            minesweeper.open_positions.is_empty(),
+            "Flagged cell isn't opened"
+        );
+
+        // ================================================
+        // Test that the flag is cleared and the cell opened in one move
+        minesweeper.force_open((1, 0));
+        assert!(minesweeper.open_positions.contains(&(1, 0)));
+        assert!(minesweeper.flagged_positions.is_empty(), "Flag cleared");
+        assert_eq!(minesweeper.moves(), 1);
+
+        // ================================================
+        // Test that a game over keeps the flags
+        minesweeper.toggle_flag((2, 0));
+        minesweeper.open((0, 0));
+        minesweeper.force_open((2, 0));
+        assert!(
+            minesweeper.flagged_positions.contains(&(2, 0)),
+            "Game is over"
+        );
+    }
 }
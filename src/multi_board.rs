@@ -0,0 +1,132 @@
+use crate::model::{FlagToggle, GameStatus, Minesweeper, Position};
+use std::fmt::{Display, Formatter};
+
+// Several boards played at once as a single game. An explosion on any board ends
+// the whole run, and the run is won once every board is cleared
+#[derive(Debug, Clone)]
+pub struct MultiBoard {
+    boards: Vec<Minesweeper>,
+}
+
+impl MultiBoard {
+    pub fn new(boards_count: usize, width: u16, height: u16, mines_count: u16) -> Self {
+        assert!(boards_count > 0, "Invalid parameters");
+        Self {
+            boards: (0..boards_count)
+                .map(|_| Minesweeper::new(width, height, mines_count))
+                .collect(),
+        }
+    }
+
+    pub fn boards(&self) -> &[Minesweeper] {
+        &self.boards
+    }
+
+    pub fn board(&self, index: usize) -> Option<&Minesweeper> {
+        self.boards.get(index)
+    }
+
+    pub fn status(&self) -> GameStatus {
+        let statuses = self.boards.iter().map(|board| board.status());
+        if statuses.clone().any(|status| status == GameStatus::Lost) {
+            GameStatus::Lost
+        } else if statuses.clone().all(|status| status == GameStatus::Won) {
+            GameStatus::Won
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    // Open a position on the board with the given index. Once the run is over
+    // no board accepts actions anymore
+    pub fn open(&mut self, index: usize, position: Position) -> &mut Self {
+        if self.status() == GameStatus::InProgress {
+            if let Some(board) = self.boards.get_mut(index) {
+                board.open(position);
+            }
+        }
+        self
+    }
+
+    pub fn toggle_flag(&mut self, index: usize, position: Position) -> Option<FlagToggle> {
+        if self.status() != GameStatus::InProgress {
+            return None;
+        }
+        self.boards.get_mut(index)?.toggle_flag(position)
+    }
+}
+
+// Boards are rendered one after another and separated by an empty line
+impl Display for MultiBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, board) in self.boards.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{board}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic = "Invalid parameters"]
+    fn test_new_invalid_parameters() {
+        MultiBoard::new(0, 10, 10, 10);
+    }
+
+    #[test]
+    fn test_open() {
+        let mut multi_board = MultiBoard::new(3, 4, 4, 1);
+        // Put a single mine in the corner of every board
+        multi_board.boards.iter_mut().for_each(|board| {
+            board.mines.drain();
+            board.set_safe_first_click(false);
+            board.mines.insert((0, 0));
+        });
+
+        // ================================================
+        // Test that actions are routed to the board with the given index
+        multi_board.open(1, (3, 3));
+        assert_eq!(multi_board.boards()[1].status(), GameStatus::Won);
+        assert_eq!(multi_board.boards()[0].open_positions.len(), 0);
+        assert_eq!(multi_board.status(), GameStatus::InProgress);
+        assert!(multi_board.toggle_flag(2, (0, 0)).is_some());
+        assert!(multi_board
+            .board(2)
+            .unwrap()
+            .flagged_positions
+            .contains(&(0, 0)));
+        assert!(
+            multi_board.toggle_flag(3, (0, 0)).is_none(),
+            "No such board"
+        );
+
+        // ================================================
+        // Test that the run is won when all boards are cleared
+        let mut won = multi_board.clone();
+        won.open(0, (3, 3)).open(2, (3, 3));
+        assert_eq!(won.status(), GameStatus::Won);
+
+        // ================================================
+        // Test that an explosion on one board ends the run
+        multi_board.open(0, (0, 0));
+        assert_eq!(multi_board.status(), GameStatus::Lost);
+        multi_board.open(2, (3, 3));
+        assert_eq!(multi_board.boards()[2].open_positions.len(), 0);
+        assert!(multi_board.toggle_flag(2, (1, 1)).is_none(), "Run is over");
+    }
+
+    #[test]
+    fn test_to_string() {
+        let multi_board = MultiBoard::new(2, 3, 2, 1);
+        let multi_board_str = multi_board.to_string();
+        let string_lines: Vec<&str> = multi_board_str.lines().collect();
+        assert_eq!(string_lines.len(), 5, "2 boards of 2 lines and a separator");
+        assert_eq!(string_lines[2], "", "Boards are separated by an empty line");
+    }
+}
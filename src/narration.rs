@@ -0,0 +1,179 @@
+// Messages for screen readers in several languages. Every message is a template with
+// `{name}` placeholders. Messages with a count have a template for every plural form of
+// the language, so frontends get grammatical text without their own plural rules.
+use crate::model::CellState;
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+// Plural form of a count. English only uses One and Many
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plural {
+    One,
+    Few,
+    Many,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Board { width: u16, height: u16 },
+    Cell(CellState),
+    MinesRemain(i32),
+    GameOver,
+}
+
+impl Language {
+    pub fn plural(self, count: u32) -> Plural {
+        match self {
+            Language::English if count == 1 => Plural::One,
+            Language::English => Plural::Many,
+            Language::Russian => match (count % 10, count % 100) {
+                (1, rest) if rest != 11 => Plural::One,
+                (2..=4, rest) if !(12..=14).contains(&rest) => Plural::Few,
+                _ => Plural::Many,
+            },
+        }
+    }
+
+    pub fn narrate(self, message: Message) -> String {
+        match message {
+            Message::Board { width, height } => fill(
+                self.template(message, Plural::Many),
+                &[("width", width as i32), ("height", height as i32)],
+            ),
+            Message::Cell(CellState::Open(count)) => self.counted(message, count as i32),
+            Message::MinesRemain(count) => self.counted(message, count),
+            Message::Cell(_) | Message::GameOver => {
+                self.template(message, Plural::Many).to_string()
+            }
+        }
+    }
+
+    // Fill the template of a message with a count, picking the plural form of the count.
+    // More flags than mines give a negative count, which has the form of its magnitude
+    fn counted(self, message: Message, count: i32) -> String {
+        let plural = self.plural(count.unsigned_abs());
+        fill(self.template(message, plural), &[("count", count)])
+    }
+
+    fn template(self, message: Message, plural: Plural) -> &'static str {
+        use Plural::*;
+        match (self, message, plural) {
+            (Language::English, Message::Board { .. }, _) => {
+                "Minesweeper board, {width} columns by {height} rows"
+            }
+            (Language::English, Message::Cell(CellState::Open(0)), _) => "no adjacent mines",
+            (Language::English, Message::Cell(CellState::Open(_)), One) => "{count} adjacent mine",
+            (Language::English, Message::Cell(CellState::Open(_)), _) => "{count} adjacent mines",
+            (Language::English, Message::Cell(cell_state), _) => match cell_state {
+                CellState::Hidden => "hidden",
+                CellState::Flagged => "flag",
+                CellState::Questioned => "question mark",
+                CellState::Mine => "mine",
+                CellState::Exploded => "exploded mine",
+                CellState::Open(_) | CellState::Masked => "open",
+            },
+            (Language::English, Message::MinesRemain(_), One) => "{count} mine remains",
+            (Language::English, Message::MinesRemain(_), _) => "{count} mines remain",
+            (Language::English, Message::GameOver, _) => "A mine exploded. Game over.",
+
+            (Language::Russian, Message::Board { .. }, _) => {
+                "Поле сапёра, столбцов: {width}, строк: {height}"
+            }
+            (Language::Russian, Message::Cell(CellState::Open(0)), _) => "рядом нет мин",
+            (Language::Russian, Message::Cell(CellState::Open(_)), One) => "{count} мина рядом",
+            (Language::Russian, Message::Cell(CellState::Open(_)), Few) => "{count} мины рядом",
+            (Language::Russian, Message::Cell(CellState::Open(_)), Many) => "{count} мин рядом",
+            (Language::Russian, Message::Cell(cell_state), _) => match cell_state {
+                CellState::Hidden => "закрыта",
+                CellState::Flagged => "флажок",
+                CellState::Questioned => "вопрос",
+                CellState::Mine => "мина",
+                CellState::Exploded => "взорванная мина",
+                CellState::Open(_) | CellState::Masked => "открыта",
+            },
+            (Language::Russian, Message::MinesRemain(_), One) => "осталась {count} мина",
+            (Language::Russian, Message::MinesRemain(_), Few) => "осталось {count} мины",
+            (Language::Russian, Message::MinesRemain(_), Many) => "осталось {count} мин",
+            (Language::Russian, Message::GameOver, _) => "Мина взорвалась. Игра окончена.",
+        }
+    }
+}
+
+// Substitute the `{name}` placeholders of the template
+fn fill(template: &str, arguments: &[(&str, i32)]) -> String {
+    arguments
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plural() {
+        assert_eq!(Language::English.plural(1), Plural::One);
+        assert_eq!(Language::English.plural(0), Plural::Many);
+        assert_eq!(Language::English.plural(21), Plural::Many);
+
+        // ================================================
+        // Test the Russian forms, where the last digits decide
+        [1, 21, 101].into_iter().for_each(|count| {
+            assert_eq!(Language::Russian.plural(count), Plural::One, "{count}");
+        });
+        [2, 4, 22, 104].into_iter().for_each(|count| {
+            assert_eq!(Language::Russian.plural(count), Plural::Few, "{count}");
+        });
+        [0, 5, 11, 12, 14, 111, 25].into_iter().for_each(|count| {
+            assert_eq!(Language::Russian.plural(count), Plural::Many, "{count}");
+        });
+    }
+
+    #[test]
+    fn test_narrate() {
+        let english = Language::English;
+        assert_eq!(english.narrate(Message::MinesRemain(1)), "1 mine remains");
+        assert_eq!(english.narrate(Message::MinesRemain(3)), "3 mines remain");
+        assert_eq!(
+            english.narrate(Message::MinesRemain(-1)),
+            "-1 mine remains",
+            "More flags than mines"
+        );
+        assert_eq!(
+            english.narrate(Message::Board {
+                width: 9,
+                height: 8
+            }),
+            "Minesweeper board, 9 columns by 8 rows"
+        );
+
+        // ================================================
+        // Test the Russian plural forms
+        let russian = Language::Russian;
+        assert_eq!(
+            russian.narrate(Message::MinesRemain(21)),
+            "осталась 21 мина"
+        );
+        assert_eq!(russian.narrate(Message::MinesRemain(3)), "осталось 3 мины");
+        assert_eq!(russian.narrate(Message::MinesRemain(11)), "осталось 11 мин");
+        assert_eq!(
+            russian.narrate(Message::Cell(CellState::Open(2))),
+            "2 мины рядом"
+        );
+        assert_eq!(
+            russian.narrate(Message::Cell(CellState::Open(0))),
+            "рядом нет мин"
+        );
+        assert_eq!(russian.narrate(Message::Cell(CellState::Flagged)), "флажок");
+    }
+}
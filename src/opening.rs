@@ -0,0 +1,126 @@
+// Advice on the first click. Every unopened cell is equally likely to hold a mine before
+// the first move, so the advisor compares how many cells a click is expected to open.
+// Corners are the most likely to start a cascade, while cells on the edges and in the
+// middle start bigger ones when they do. Which wins depends on the density, so it is
+// measured on random layouts of the same size and mine count. With the safe first click
+// on, the mines of the samples move away from the click like they would in the game.
+use crate::model::{GameStatus, Minesweeper, Position};
+use rand::{seq::index, Rng};
+
+// Random layouts sampled by `suggest_opening`
+pub const OPENING_SAMPLES: usize = 200;
+
+impl Minesweeper {
+    // Recommend the first click, or None once the game has started
+    pub fn suggest_opening(&self) -> Option<Position> {
+        self.suggest_opening_with(&mut rand::thread_rng(), OPENING_SAMPLES)
+    }
+
+    pub fn suggest_opening_with(&self, rng: &mut impl Rng, samples: usize) -> Option<Position> {
+        if !self.open_positions.is_empty() || self.status() != GameStatus::InProgress {
+            return None;
+        }
+
+        let (width, height) = (self.width(), self.height());
+        let candidates = self.opening_candidates();
+        let mut opened = vec![0; candidates.len()];
+        for _ in 0..samples {
+            let cells = width as usize * height as usize;
+            let layout = index::sample(rng, cells, self.mines.len())
+                .into_iter()
+                .map(|index| {
+                    (
+                        (index % width as usize) as u16,
+                        (index / width as usize) as u16,
+                    )
+                });
+            let mut game = Minesweeper::from_mines(width, height, layout);
+            game.set_safe_first_click(self.safe_first_click());
+            // Seeded from the generator, so the mines move the same way for every candidate
+            game.seed = Some(rng.gen());
+
+            let snapshot = game.snapshot();
+            candidates
+                .iter()
+                .zip(&mut opened)
+                .for_each(|(&candidate, opened)| {
+                    let mut game = snapshot.sandbox();
+                    game.open(candidate);
+                    if !game.game_over {
+                        *opened += game.open_positions.len();
+                    }
+                });
+        }
+
+        // Prefer the earlier candidate on ties
+        candidates
+            .into_iter()
+            .zip(opened)
+            .rev()
+            .max_by_key(|&(_, opened)| opened)
+            .map(|(candidate, _)| candidate)
+    }
+
+    // One cell of every kind which behaves differently: a corner, the middles of the
+    // edges, the cell diagonal to the corner and the center
+    fn opening_candidates(&self) -> Vec<Position> {
+        let (width, height) = (self.width(), self.height());
+        let mut candidates = Vec::new();
+        [
+            (0, 0),
+            (width / 2, 0),
+            (0, height / 2),
+            (1.min(width - 1), 1.min(height - 1)),
+            (width / 2, height / 2),
+        ]
+        .into_iter()
+        .filter(|position| !self.flagged_positions.contains(position))
+        .for_each(|position| {
+            if !candidates.contains(&position) {
+                candidates.push(position);
+            }
+        });
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_suggest_opening() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // ================================================
+        // Test that dense boards start in the corner, where a cascade is most likely
+        let mut minesweeper = Minesweeper::new(16, 16, 99);
+        minesweeper.set_safe_first_click(false);
+        assert_eq!(
+            minesweeper.suggest_opening_with(&mut rng, 1000),
+            Some((0, 0))
+        );
+
+        // ================================================
+        // Test that with the safe first click the middle wins, since every click opens an
+        // area and more of it is on the board
+        let minesweeper = Minesweeper::new(16, 16, 99);
+        assert_eq!(
+            minesweeper.suggest_opening_with(&mut rng, 100),
+            Some((8, 8))
+        );
+
+        // ================================================
+        // Test that flagged cells aren't suggested
+        let mut minesweeper = Minesweeper::new(16, 16, 99);
+        minesweeper.toggle_flag((0, 0));
+        assert_ne!(minesweeper.suggest_opening_with(&mut rng, 10), Some((0, 0)));
+
+        // ================================================
+        // Test that there is no advice once the game started
+        let mut minesweeper = crate::board!["*..", "...", "..."];
+        minesweeper.open((2, 2));
+        assert_eq!(minesweeper.suggest_opening(), None);
+    }
+}
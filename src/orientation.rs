@@ -0,0 +1,192 @@
+// Query for non-visual play. Given the cursor, it describes the cell under it, the
+// surrounding numbers and the nearest frontier cells, with distances and directions an
+// audio frontend can narrate or turn into sound. Distances count cursor moves with
+// diagonals, and north is the top of the board.
+use crate::{
+    aria::cell_label,
+    model::{Minesweeper, Position},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Orientation {
+    pub x: u16,
+    pub y: u16,
+    pub label: String,
+    // The cells around the cursor, clockwise from the north
+    pub neighbours: Vec<Bearing>,
+    // The closest frontier cells, nearest first
+    pub nearest_frontier: Vec<Bearing>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bearing {
+    pub x: u16,
+    pub y: u16,
+    pub distance: u16,
+    pub direction: Direction,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    // The direction from one position to another, in one of eight 45 degree sectors.
+    // Returns None for the same position
+    pub fn between((from_x, from_y): Position, (to_x, to_y): Position) -> Option<Self> {
+        let dx = to_x as i32 - from_x as i32;
+        let dy = to_y as i32 - from_y as i32;
+        // tan(67.5°) ≈ 12 / 5 splits the straight directions from the diagonals
+        let horizontal = 5 * dx.abs() > 12 * dy.abs();
+        let vertical = 5 * dy.abs() > 12 * dx.abs();
+        let direction = match (dx.signum(), dy.signum()) {
+            (0, 0) => return None,
+            (_, -1) if vertical => Self::North,
+            (_, 1) if vertical => Self::South,
+            (1, _) if horizontal => Self::East,
+            (-1, _) if horizontal => Self::West,
+            (1, -1) => Self::NorthEast,
+            (1, _) => Self::SouthEast,
+            (_, 1) => Self::SouthWest,
+            _ => Self::NorthWest,
+        };
+        Some(direction)
+    }
+}
+
+impl Minesweeper {
+    // Describe the surroundings of the cursor with up to `limit` nearest frontier cells
+    pub fn orientation(&self, cursor: Position, limit: usize) -> Orientation {
+        let bearing = |position: Position| Bearing {
+            x: position.0,
+            y: position.1,
+            distance: position
+                .0
+                .abs_diff(cursor.0)
+                .max(position.1.abs_diff(cursor.1)),
+            direction: Direction::between(cursor, position).unwrap_or(Direction::North),
+            label: cell_label(self.cell_state(position)),
+        };
+
+        let mut neighbours: Vec<Bearing> = self.neighbours(cursor).map(bearing).collect();
+        neighbours.sort_by_key(|bearing| bearing.direction);
+
+        let mut nearest_frontier: Vec<Bearing> = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter(|&position| position != cursor && self.is_frontier(position))
+            .map(bearing)
+            .collect();
+        nearest_frontier
+            .sort_by_key(|bearing| (bearing.distance, bearing.direction, bearing.x, bearing.y));
+        nearest_frontier.truncate(limit);
+
+        Orientation {
+            x: cursor.0,
+            y: cursor.1,
+            label: cell_label(self.cell_state(cursor)),
+            neighbours,
+            nearest_frontier,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_between() {
+        let center = (5, 5);
+        assert_eq!(Direction::between(center, center), None);
+        assert_eq!(Direction::between(center, (5, 0)), Some(Direction::North));
+        assert_eq!(
+            Direction::between(center, (6, 4)),
+            Some(Direction::NorthEast)
+        );
+        assert_eq!(
+            Direction::between(center, (9, 6)),
+            Some(Direction::East),
+            "Mostly east"
+        );
+        assert_eq!(
+            Direction::between(center, (7, 9)),
+            Some(Direction::SouthEast)
+        );
+        assert_eq!(
+            Direction::between(center, (4, 9)),
+            Some(Direction::South),
+            "Mostly south"
+        );
+        assert_eq!(
+            Direction::between(center, (0, 9)),
+            Some(Direction::SouthWest)
+        );
+        assert_eq!(Direction::between(center, (0, 5)), Some(Direction::West));
+        assert_eq!(
+            Direction::between(center, (3, 3)),
+            Some(Direction::NorthWest)
+        );
+    }
+
+    #[test]
+    fn test_orientation() {
+        // ================================================
+        // Test the surroundings of an open cell
+        //
+        //     * 1 . . .
+        //     . . . . .
+        //     . . . . .
+        let minesweeper = crate::board!["*o...", ".....", "....."];
+        let orientation = minesweeper.orientation((1, 0), 2);
+        assert_eq!(orientation.label, "1 adjacent mine");
+        let directions: Vec<Direction> = orientation
+            .neighbours
+            .iter()
+            .map(|bearing| bearing.direction)
+            .collect();
+        assert_eq!(
+            directions,
+            vec![
+                Direction::East,
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::West
+            ]
+        );
+
+        // ================================================
+        // Test the nearest frontier cells from a distant cursor
+        let orientation = minesweeper.orientation((4, 2), 2);
+        assert_eq!(
+            orientation.nearest_frontier,
+            vec![
+                Bearing {
+                    x: 2,
+                    y: 0,
+                    distance: 2,
+                    direction: Direction::NorthWest,
+                    label: "hidden".to_string(),
+                },
+                Bearing {
+                    x: 2,
+                    y: 1,
+                    distance: 2,
+                    direction: Direction::NorthWest,
+                    label: "hidden".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,650 @@
+// Partition of the unknown cells as the player sees them. Hidden and flagged cells next
+// to an open number form the frontier. The frontier splits into independent components,
+// groups of cells linked by the numbers around them. The mines of one component don't
+// affect the others. Every other unknown cell belongs to the "sea", which no number
+// touches.
+use crate::{
+    hash::{HashMap, HashSet},
+    model::{CellState, Minesweeper, Position},
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Partition {
+    pub components: Vec<FrontierComponent>,
+    pub sea: Vec<Position>,
+    // Bounds of the mines in the sea, derived from the total and the component bounds
+    pub sea_min_mines: usize,
+    pub sea_max_mines: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FrontierComponent {
+    pub cells: Vec<Position>,
+    // Fewest and most mines the cells hold in any layout consistent with the numbers
+    pub min_mines: usize,
+    pub max_mines: usize,
+    // Cells without a mine in every consistent layout
+    pub safe: Vec<Position>,
+    // Cells with a mine in every consistent layout
+    pub mines: Vec<Position>,
+    // False if the search hit the step limit. The bounds are then 0 and the number of
+    // cells, and no cell is known to be safe or a mine
+    pub solved: bool,
+}
+
+// An open number and the unknown cells around it
+struct Constraint {
+    mines: usize,
+    cells: Vec<Position>,
+}
+
+// The search of a component stops after this many steps and leaves it unsolved, so a huge
+// frontier can't stall a click or a frame. An open judged on it counts as a guess
+const STEP_LIMIT: usize = 100_000;
+
+// The numbers around the unknown cells, before the components are solved
+struct Frontier {
+    constraints: Vec<Constraint>,
+    // Frontier cells with the indices of the constraints they are part of
+    cells: BTreeMap<Position, Vec<usize>>,
+    sea: Vec<Position>,
+}
+
+impl Minesweeper {
+    pub fn partition(&self) -> Partition {
+        let frontier = self.frontier();
+        let mut seen = HashSet::default();
+        let mut components = Vec::new();
+        for &start in frontier.cells.keys() {
+            if !seen.contains(&start) {
+                let cells = frontier.component(start, &mut seen);
+                components.push(frontier.solve(cells));
+            }
+        }
+
+        let total = self.mines.len();
+        let frontier_min: usize = components.iter().map(|c| c.min_mines).sum();
+        let frontier_max: usize = components.iter().map(|c| c.max_mines).sum();
+        Partition {
+            sea_min_mines: total.saturating_sub(frontier_max),
+            sea_max_mines: total.saturating_sub(frontier_min).min(frontier.sea.len()),
+            components,
+            sea: frontier.sea,
+        }
+    }
+
+    // Whether the numbers on the board prove that the position has no mine. Only the
+    // numbers around a frontier cell are taken into account, so a few deductions which
+    // also need the total mine count are missed
+    pub fn is_provably_safe(&self, position: Position) -> bool {
+        self.provably_safe_cells(&[position]).contains(&position)
+    }
+
+    // The targets which the numbers prove safe, like `is_provably_safe` but with a single
+    // scan of the board. Only the components of the targets are solved, unless a target
+    // is in the sea, and a search which hits the step limit proves nothing
+    pub(crate) fn provably_safe_cells(&self, targets: &[Position]) -> Vec<Position> {
+        let frontier = self.frontier();
+        let total = self.mines.len();
+        // The sea is safe once the frontier holds every mine, which takes every component
+        let sea_needs_check = frontier.cells.len() >= total
+            && targets
+                .iter()
+                .any(|target| frontier.sea.binary_search(target).is_ok());
+        let starts: Vec<Position> = if sea_needs_check {
+            frontier.cells.keys().copied().collect()
+        } else {
+            targets
+                .iter()
+                .filter(|target| frontier.cells.contains_key(target))
+                .copied()
+                .collect()
+        };
+
+        let mut seen = HashSet::default();
+        let mut components = Vec::new();
+        for start in starts {
+            if !seen.contains(&start) {
+                let cells = frontier.component(start, &mut seen);
+                components.push(frontier.solve(cells));
+            }
+        }
+        let sea_is_safe = sea_needs_check
+            && components
+                .iter()
+                .map(|component| component.min_mines)
+                .sum::<usize>()
+                >= total;
+
+        targets
+            .iter()
+            .filter(|target| {
+                components
+                    .iter()
+                    .any(|component| component.safe.binary_search(target).is_ok())
+                    || (sea_is_safe && frontier.sea.binary_search(target).is_ok())
+            })
+            .copied()
+            .collect()
+    }
+
+    // Share of the layouts of the whole board with a mine on the position. Unlike the
+    // component counts, every layout of the frontier is weighted by the ways to place
+    // the remaining mines in the sea, so the mine total is taken into account. None if
+    // the position isn't unknown or a search hits the step limit
+    pub(crate) fn mine_odds(&self, position: Position) -> Option<f64> {
+        self.all_mine_odds()?.get(&position).copied()
+    }
+
+    // The odds of `mine_odds` for every unknown cell, from a single scan of the board
+    pub(crate) fn all_mine_odds(&self) -> Option<HashMap<Position, f64>> {
+        let frontier = self.frontier();
+        let mut seen = HashSet::default();
+        let mut searches = Vec::new();
+        for &start in frontier.cells.keys() {
+            if !seen.contains(&start) {
+                let cells = frontier.component(start, &mut seen);
+                let search = frontier.search(&cells, true)?;
+                searches.push((cells, search));
+            }
+        }
+
+        // The frontier layouts by their number of mines, of the components before every
+        // component and of the components from it on. Scaled down after every component,
+        // only their ratios matter
+        let counts: Vec<Vec<f64>> = searches
+            .iter()
+            .map(|(_, search)| to_f64(&search.layouts_by_mines))
+            .collect();
+        let mut before = vec![vec![1.0]];
+        for count in &counts {
+            before.push(scaled(convolve(&before[before.len() - 1], count)));
+        }
+        let mut from = vec![vec![1.0]];
+        for count in counts.iter().rev() {
+            from.push(scaled(convolve(&from[from.len() - 1], count)));
+        }
+        from.reverse();
+        let layouts = &before[counts.len()];
+
+        // Ways to place the remaining mines in the sea, relative to the most of them, by
+        // the number of mines on the frontier
+        let sea = frontier.sea.len();
+        let ln_factorials: Vec<f64> = std::iter::once(0.0)
+            .chain((1..=sea).scan(0.0, |sum, n| {
+                *sum += (n as f64).ln();
+                Some(*sum)
+            }))
+            .collect();
+        let ln_ways: Vec<Option<f64>> = (0..layouts.len())
+            .map(|frontier_mines| {
+                let sea_mines = self.mines.len().checked_sub(frontier_mines)?;
+                (sea_mines <= sea).then(|| {
+                    ln_factorials[sea] - ln_factorials[sea_mines] - ln_factorials[sea - sea_mines]
+                })
+            })
+            .collect();
+        let most = ln_ways.iter().flatten().copied().fold(f64::MIN, f64::max);
+        let ways: Vec<f64> = ln_ways
+            .iter()
+            .map(|ln_ways| ln_ways.map_or(0.0, |ln_ways| (ln_ways - most).exp()))
+            .collect();
+
+        let mut odds = HashMap::default();
+        for (index, (cells, search)) in searches.iter().enumerate() {
+            // The weight of the layouts of the component by their number of mines, summed
+            // over the layouts of the other components
+            let others = convolve(&before[index], &from[index + 1]);
+            let weights: Vec<f64> = (0..counts[index].len())
+                .map(|mines| {
+                    others
+                        .iter()
+                        .enumerate()
+                        .map(|(other_mines, &count)| count * ways[mines + other_mines])
+                        .sum()
+                })
+                .collect();
+            let weigh = |counts: &[f64]| -> f64 {
+                counts
+                    .iter()
+                    .zip(&weights)
+                    .map(|(count, weight)| count * weight)
+                    .sum()
+            };
+            let total = weigh(&counts[index]);
+            if total <= 0.0 {
+                return None;
+            }
+            cells
+                .iter()
+                .zip(&search.cell_layouts_by_mines)
+                .for_each(|(&cell, mine_layouts)| {
+                    odds.insert(cell, weigh(&to_f64(mine_layouts)) / total);
+                });
+        }
+
+        // Every sea cell has a mine in the same share of the sea placements
+        let (mut total, mut mined) = (0.0, 0.0);
+        for (frontier_mines, (&count, &ways)) in layouts.iter().zip(&ways).enumerate() {
+            total += count * ways;
+            if sea > 0 {
+                let sea_mines = self.mines.len().saturating_sub(frontier_mines);
+                mined += count * ways * sea_mines as f64 / sea as f64;
+            }
+        }
+        if total <= 0.0 {
+            return None;
+        }
+        frontier.sea.iter().for_each(|&cell| {
+            odds.insert(cell, mined / total);
+        });
+        Some(odds)
+    }
+
+    // Whether the position is unknown and next to an open number
+    pub(crate) fn is_frontier(&self, position: Position) -> bool {
+        is_unknown(self.cell_state(position))
+            && self
+                .neighbours(position)
+                .any(|neighbour| matches!(self.cell_state(neighbour), CellState::Open(_)))
+    }
+
+    fn frontier(&self) -> Frontier {
+        let mut constraints = Vec::new();
+        let mut cells: BTreeMap<Position, Vec<usize>> = BTreeMap::new();
+        let mut sea = Vec::new();
+        for x in 0..self.width() {
+            for y in 0..self.height() {
+                let position = (x, y);
+                if is_unknown(self.cell_state(position)) {
+                    sea.push(position);
+                    continue;
+                }
+                let CellState::Open(mines) = self.cell_state(position) else {
+                    continue;
+                };
+                let unknown: Vec<Position> = self
+                    .neighbours(position)
+                    .filter(|&neighbour| is_unknown(self.cell_state(neighbour)))
+                    .collect();
+                if unknown.is_empty() {
+                    continue;
+                }
+                unknown.iter().for_each(|&cell| {
+                    cells.entry(cell).or_default().push(constraints.len());
+                });
+                constraints.push(Constraint {
+                    mines: mines as usize,
+                    cells: unknown,
+                });
+            }
+        }
+        sea.retain(|position| !cells.contains_key(position));
+        Frontier {
+            constraints,
+            cells,
+            sea,
+        }
+    }
+}
+
+fn is_unknown(state: CellState) -> bool {
+    matches!(
+        state,
+        CellState::Hidden | CellState::Flagged | CellState::Questioned
+    )
+}
+
+// The counts of two independent groups of cells by their number of mines, combined
+fn convolve(first: &[f64], second: &[f64]) -> Vec<f64> {
+    let mut combined = vec![0.0; first.len() + second.len() - 1];
+    for (i, &a) in first.iter().enumerate() {
+        for (j, &b) in second.iter().enumerate() {
+            combined[i + j] += a * b;
+        }
+    }
+    combined
+}
+
+// Counts scaled down to at most 1, so that the product of many components stays finite
+fn scaled(mut counts: Vec<f64>) -> Vec<f64> {
+    let most = counts.iter().copied().fold(0.0, f64::max);
+    counts.iter_mut().for_each(|count| *count /= most);
+    counts
+}
+
+fn to_f64(counts: &[u64]) -> Vec<f64> {
+    counts.iter().map(|&count| count as f64).collect()
+}
+
+impl Frontier {
+    // The cells linked to the start cell, marked as seen. They come in breadth-first
+    // order, which keeps the cells of every constraint close together for the search
+    fn component(&self, start: Position, seen: &mut HashSet<Position>) -> Vec<Position> {
+        let mut cells = vec![start];
+        let mut queue = VecDeque::from([start]);
+        seen.insert(start);
+        while let Some(cell) = queue.pop_front() {
+            for &index in &self.cells[&cell] {
+                for &linked in &self.constraints[index].cells {
+                    if seen.insert(linked) {
+                        cells.push(linked);
+                        queue.push_back(linked);
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    fn solve(&self, mut cells: Vec<Position>) -> FrontierComponent {
+        let Some(search) = self.search(&cells, false) else {
+            cells.sort();
+            return FrontierComponent {
+                min_mines: 0,
+                max_mines: cells.len(),
+                safe: Vec::new(),
+                mines: Vec::new(),
+                solved: false,
+                cells,
+            };
+        };
+        let (min_mines, max_mines) = search.bounds;
+        let mut safe: Vec<Position> = cells
+            .iter()
+            .zip(search.can_be_mine)
+            .filter(|&(_, can_be_mine)| !can_be_mine)
+            .map(|(&cell, _)| cell)
+            .collect();
+        safe.sort();
+        let mut mines: Vec<Position> = cells
+            .iter()
+            .zip(search.can_be_safe)
+            .filter(|&(_, can_be_safe)| !can_be_safe)
+            .map(|(&cell, _)| cell)
+            .collect();
+        mines.sort();
+        cells.sort();
+        FrontierComponent {
+            cells,
+            min_mines,
+            max_mines,
+            safe,
+            mines,
+            solved: true,
+        }
+    }
+
+    // Every number is checked as soon as one of its cells is decided, which prunes the
+    // search early. The real layout always satisfies the numbers, so there is a solution.
+    // Returns None if the search takes more steps than the limit. The layouts of every cell
+    // by their number of mines are only counted for the odds, they take a lot of memory
+    fn search(&self, cells: &[Position], by_cell: bool) -> Option<Search<'_>> {
+        let mut search = Search {
+            cell_constraints: cells
+                .iter()
+                .map(|cell| self.cells[cell].as_slice())
+                .collect(),
+            constraints: &self.constraints,
+            placed: vec![0; self.constraints.len()],
+            undecided: self.constraints.iter().map(|c| c.cells.len()).collect(),
+            layout: vec![false; cells.len()],
+            steps_left: STEP_LIMIT,
+            bounds: (usize::MAX, 0),
+            can_be_mine: vec![false; cells.len()],
+            can_be_safe: vec![false; cells.len()],
+            layouts_by_mines: vec![0; cells.len() + 1],
+            cell_layouts_by_mines: if by_cell {
+                vec![vec![0; cells.len() + 1]; cells.len()]
+            } else {
+                Vec::new()
+            },
+        };
+        search.run(0, 0);
+        (search.steps_left > 0).then_some(search)
+    }
+}
+
+// Search of all mine layouts of the component cells which satisfy the numbers
+struct Search<'a> {
+    cell_constraints: Vec<&'a [usize]>,
+    constraints: &'a [Constraint],
+    // Mines placed and cells still undecided around every number
+    placed: Vec<usize>,
+    undecided: Vec<usize>,
+    // The layout being built
+    layout: Vec<bool>,
+    // Steps the search may still take, it gives up at 0
+    steps_left: usize,
+    // Results: the mine count bounds and the cells with a mine or without one in some
+    // layout
+    bounds: (usize, usize),
+    can_be_mine: Vec<bool>,
+    can_be_safe: Vec<bool>,
+    // The layouts by their number of mines, in total and, if asked for, with a mine on
+    // every cell
+    layouts_by_mines: Vec<u64>,
+    cell_layouts_by_mines: Vec<Vec<u64>>,
+}
+
+impl Search<'_> {
+    fn run(&mut self, index: usize, mines: usize) {
+        if self.steps_left == 0 {
+            return;
+        }
+        self.steps_left -= 1;
+        if index == self.cell_constraints.len() {
+            self.bounds = (self.bounds.0.min(mines), self.bounds.1.max(mines));
+            self.layout
+                .iter()
+                .zip(self.can_be_mine.iter_mut().zip(&mut self.can_be_safe))
+                .for_each(|(&is_mine, (can_be_mine, can_be_safe))| {
+                    *can_be_mine |= is_mine;
+                    *can_be_safe |= !is_mine;
+                });
+            self.layouts_by_mines[mines] += 1;
+            self.layout
+                .iter()
+                .zip(&mut self.cell_layouts_by_mines)
+                .filter(|(&is_mine, _)| is_mine)
+                .for_each(|(_, mine_layouts)| mine_layouts[mines] += 1);
+            return;
+        }
+        for is_mine in [false, true] {
+            let touched = self.cell_constraints[index];
+            touched.iter().for_each(|&c| {
+                self.undecided[c] -= 1;
+                self.placed[c] += is_mine as usize;
+            });
+            let is_consistent = touched.iter().all(|&c| {
+                self.placed[c] <= self.constraints[c].mines
+                    && self.placed[c] + self.undecided[c] >= self.constraints[c].mines
+            });
+            if is_consistent {
+                self.layout[index] = is_mine;
+                self.run(index + 1, mines + is_mine as usize);
+            }
+            touched.iter().for_each(|&c| {
+                self.undecided[c] += 1;
+                self.placed[c] -= is_mine as usize;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition() {
+        // ================================================
+        // Test a board without open cells: everything is sea
+        let minesweeper = crate::board!["*..", "...", "..*"];
+        let partition = minesweeper.partition();
+        assert!(partition.components.is_empty(), "No frontier");
+        assert_eq!(partition.sea.len(), 9);
+        assert_eq!((partition.sea_min_mines, partition.sea_max_mines), (2, 2));
+
+        // ================================================
+        // Test two independent components and the sea
+        //
+        //     * 1 . . . .
+        //     . 1 . . 1 *
+        //     . . . . . .
+        let minesweeper = crate::board!["*o....", ".o..o*", "......"];
+        let partition = minesweeper.partition();
+        assert_eq!(partition.components.len(), 2, "2 components");
+        assert_eq!(
+            partition.components[0],
+            FrontierComponent {
+                cells: vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 0), (2, 1), (2, 2)],
+                min_mines: 1,
+                max_mines: 1,
+                safe: vec![(0, 2), (1, 2), (2, 2)],
+                mines: vec![],
+                solved: true,
+            }
+        );
+        assert_eq!(
+            partition.components[1].cells,
+            vec![
+                (3, 0),
+                (3, 1),
+                (3, 2),
+                (4, 0),
+                (4, 2),
+                (5, 0),
+                (5, 1),
+                (5, 2)
+            ]
+        );
+        assert_eq!(
+            (
+                partition.components[1].min_mines,
+                partition.components[1].max_mines
+            ),
+            (1, 1)
+        );
+        assert!(
+            partition.sea.is_empty(),
+            "Every unknown cell is on the frontier"
+        );
+        assert_eq!((partition.sea_min_mines, partition.sea_max_mines), (0, 0));
+
+        // ================================================
+        // Test that flagged cells are unknown and the sea bounds
+        let mut minesweeper = crate::board!["o*...", "F....", ".....", "....*"];
+        minesweeper.toggle_flag((1, 0));
+        let partition = minesweeper.partition();
+        assert_eq!(partition.components.len(), 1);
+        assert_eq!(partition.components[0].cells, vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(
+            (
+                partition.components[0].min_mines,
+                partition.components[0].max_mines
+            ),
+            (2, 2)
+        );
+        assert_eq!(partition.sea.len(), 16);
+        assert_eq!((partition.sea_min_mines, partition.sea_max_mines), (1, 1));
+
+        // ================================================
+        // Test a component whose mine count depends on the layout: a mine in the
+        // middle satisfies both numbers, or two mines at the ends do
+        let minesweeper = crate::board!["*o.o*"];
+        let partition = minesweeper.partition();
+        assert_eq!(partition.components.len(), 1);
+        assert_eq!(
+            (
+                partition.components[0].min_mines,
+                partition.components[0].max_mines
+            ),
+            (1, 2)
+        );
+        assert!(partition.sea.is_empty());
+    }
+
+    #[test]
+    fn test_is_provably_safe() {
+        let minesweeper = crate::board!["*o....", ".o..o*", "......"];
+        assert!(
+            minesweeper.is_provably_safe((1, 2)),
+            "Both numbers are satisfied"
+        );
+        assert!(!minesweeper.is_provably_safe((0, 0)), "Might be a mine");
+        assert!(!minesweeper.is_provably_safe((1, 0)), "Already open");
+
+        // ================================================
+        // Test that the sea is safe once the frontier holds every mine
+        let minesweeper = crate::board!["*o", "oo", "..", ".."];
+        assert!(minesweeper.is_provably_safe((0, 2)), "Frontier");
+        assert!(minesweeper.is_provably_safe((0, 3)), "Sea");
+        assert!(!minesweeper.is_provably_safe((0, 0)), "The mine");
+    }
+
+    #[test]
+    fn test_mine_odds() {
+        // A mine in the middle leaves 2 ways to place the other one in the sea, two mines
+        // at the ends leave 1
+        let minesweeper = crate::board!["*o.o*.."];
+        let odds = |position| minesweeper.mine_odds(position).unwrap();
+        assert!((odds((2, 0)) - 2.0 / 3.0).abs() < 1e-9, "Middle");
+        assert!((odds((0, 0)) - 1.0 / 3.0).abs() < 1e-9, "End");
+        assert!((odds((5, 0)) - 1.0 / 3.0).abs() < 1e-9, "Sea");
+        assert_eq!(minesweeper.mine_odds((1, 0)), None, "Open");
+
+        // ================================================
+        // Test that the sea is safe once the frontier holds every mine
+        let minesweeper = crate::board!["*o", "oo", "..", ".."];
+        assert_eq!(minesweeper.mine_odds((0, 3)), Some(0.0));
+        assert_eq!(minesweeper.mine_odds((0, 0)), Some(1.0));
+
+        // ================================================
+        // Test independent components, which hold one mine each
+        let minesweeper = crate::board!["*o..o*.."];
+        let odds = minesweeper.all_mine_odds().unwrap();
+        assert_eq!(odds.len(), 6, "Unknown cells");
+        assert!((odds[&(2, 0)] - 0.5).abs() < 1e-9, "First component");
+        assert!((odds[&(5, 0)] - 0.5).abs() < 1e-9, "Second component");
+        assert_eq!(odds[&(7, 0)], 0.0, "Sea");
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // Row 1 is open and every third cell of row 0 has a mine. Only the numbers at the
+        // right end rule out the layouts with the mines one cell to the right, so the
+        // cells between the mines are safe but the search has to go through them all
+        let layout = |mines: usize| {
+            let width = 3 * mines + 1;
+            crate::test_util::from_layout(&[
+                &("*..".repeat(mines) + "*"),
+                &"o".repeat(width),
+                &".".repeat(width),
+            ])
+        };
+        assert!(layout(4).is_provably_safe((1, 0)), "Small component");
+
+        // ================================================
+        // Test that a search over the step limit counts the open as a guess
+        let mut minesweeper = layout(20);
+        minesweeper.set_safe_first_click(false);
+        assert!(!minesweeper.is_provably_safe((1, 0)), "Step limit");
+        minesweeper.open((1, 0));
+        assert_eq!(minesweeper.luck_stats().guesses, 1);
+        assert_eq!(minesweeper.luck_stats().deductions, 0);
+
+        // ================================================
+        // Test that a frontier far too large to search is left unsolved
+        let partition = layout(40).partition();
+        assert_eq!(partition.components.len(), 1);
+        let component = &partition.components[0];
+        assert!(!component.solved, "Step limit");
+        assert_eq!(component.cells.len(), 2 * 121);
+        assert_eq!((component.min_mines, component.max_mines), (0, 2 * 121));
+        assert!(component.safe.is_empty() && component.mines.is_empty());
+        assert!(layout(4).partition().components[0].solved);
+    }
+}
@@ -0,0 +1,140 @@
+// Probes for casual games: the player spends a probe to learn whether a cell holds a mine
+// without risking the game. A probed mine gets flagged, unless the flag cap leaves no
+// flag for it, and a probed safe cell is opened. Every probe is a move of the history,
+// and the number of probes spent is kept as the penalty for scoring. Undoing a probe
+// doesn't give it back, the player still knows what it revealed.
+use crate::{
+    action::Action,
+    model::{Minesweeper, Position},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeResult {
+    Mine,
+    Safe,
+}
+
+impl Minesweeper {
+    // Set the number of probes the player may spend. Games start without probes
+    pub fn set_probes(&mut self, probes: u32) {
+        self.probes_left = probes;
+    }
+
+    pub fn probes_left(&self) -> u32 {
+        self.probes_left
+    }
+
+    pub fn probes_used(&self) -> u32 {
+        self.probes_used
+    }
+
+    // Spend a probe on a hidden or flagged cell. Return None without spending anything if
+    // there are no probes left, the game is over or the cell is open or outside the board
+    pub fn probe(&mut self, position: Position) -> Option<ProbeResult> {
+        if self.probes_left == 0 || !self.can_be_marked(position) {
+            return None;
+        }
+
+        self.probes_left -= 1;
+        self.probes_used += 1;
+        let result = self.record(Action::Probe(position), |minesweeper| {
+            if minesweeper.mines.contains(&position) {
+                if minesweeper.has_flags_left() {
+                    // The flag replaces a question mark
+                    minesweeper.questioned_positions.remove(&position);
+                    minesweeper.flagged_positions.insert(position);
+                }
+                minesweeper.moves += 1;
+                minesweeper.debug_audit();
+                ProbeResult::Mine
+            } else {
+                minesweeper.flagged_positions.remove(&position);
+                // The probe proved the cell safe, so opening it is neither luck nor skill
+                minesweeper.open_unjudged(position);
+                ProbeResult::Safe
+            }
+        });
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CellState, GameStatus};
+
+    #[test]
+    fn test_probe() {
+        let mut minesweeper = crate::board!["*..", "f..", "..*"];
+        assert_eq!(minesweeper.probe((0, 0)), None, "No probes");
+
+        // ================================================
+        // Test that a probed mine is flagged and a safe cell is opened
+        minesweeper.set_probes(3);
+        assert_eq!(minesweeper.probe((0, 0)), Some(ProbeResult::Mine));
+        assert!(minesweeper.flagged_positions.contains(&(0, 0)));
+        assert!(!minesweeper.game_over, "Probing a mine is harmless");
+        assert_eq!(minesweeper.probe((0, 1)), Some(ProbeResult::Safe));
+        assert!(minesweeper.open_positions.contains(&(0, 1)));
+        assert!(
+            !minesweeper.flagged_positions.contains(&(0, 1)),
+            "Wrong flag removed"
+        );
+        assert_eq!(minesweeper.luck_stats().guesses, 0, "Probes aren't guesses");
+
+        // ================================================
+        // Test the cells which can't be probed
+        assert_eq!(minesweeper.probe((0, 1)), None, "Already open");
+        assert_eq!(minesweeper.probe((3, 0)), None, "Outside the board");
+        assert_eq!(minesweeper.probes_left(), 1);
+        assert_eq!(minesweeper.probes_used(), 2);
+        assert_eq!(minesweeper.moves(), 2, "Every probe is a move");
+        assert_eq!(minesweeper.history()[0].action, Action::Probe((0, 0)));
+
+        // ================================================
+        // Test that undoing a probe doesn't give it back
+        minesweeper.undo();
+        assert_eq!(minesweeper.cell_state((0, 1)), CellState::Flagged);
+        assert_eq!(minesweeper.probes_left(), 1, "Still spent");
+        minesweeper.redo();
+        assert_eq!(minesweeper.cell_state((0, 1)), CellState::Open(1));
+        assert_eq!(minesweeper.probes_used(), 2);
+
+        // ================================================
+        // Test that a won game can't be probed
+        minesweeper
+            .apply(Action::Open((2, 0)))
+            .apply(Action::Open((0, 2)));
+        assert_eq!(minesweeper.status(), GameStatus::Won);
+        assert_eq!(minesweeper.probe((2, 2)), None, "Game is won");
+    }
+
+    #[test]
+    fn test_probe_flag_cap() {
+        // ================================================
+        // Test that a probed mine isn't flagged without flags left
+        let mut minesweeper = crate::board!["*f.", "...", "..."];
+        minesweeper.set_flag_cap(true);
+        minesweeper.set_probes(1);
+        assert_eq!(minesweeper.probe((0, 0)), Some(ProbeResult::Mine));
+        assert_eq!(minesweeper.cell_state((0, 0)), CellState::Hidden);
+        assert_eq!(minesweeper.moves(), 1, "Still a move");
+        assert_eq!(minesweeper.audit(), Ok(()));
+    }
+
+    #[test]
+    fn test_probe_questioned() {
+        // ================================================
+        // Test that a probed mine loses its question mark and the game still loads
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_probes(1);
+        minesweeper.cycle_mark((0, 0));
+        minesweeper.cycle_mark((0, 0));
+        assert_eq!(minesweeper.probe((0, 0)), Some(ProbeResult::Mine));
+        assert_eq!(minesweeper.cell_state((0, 0)), CellState::Flagged);
+        assert_eq!(minesweeper.audit(), Ok(()));
+        assert!(Minesweeper::deserialize(&minesweeper.serialize()).is_ok());
+    }
+}
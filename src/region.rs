@@ -0,0 +1,197 @@
+// Campaign boards: the board is divided into named rectangular regions which unlock one
+// after another. A region becomes playable once every safe cell of the previous region
+// is open. Cells of locked regions can't be opened, flagged or probed, and cascades stop
+// at their border. Cells outside every region are always playable.
+use crate::model::{Minesweeper, Position};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub name: String,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Region {
+    pub fn contains(&self, (x, y): Position) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    fn overlaps(&self, other: &Region) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (self.x..self.x + self.width)
+            .flat_map(move |x| (self.y..self.y + self.height).map(move |y| (x, y)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionError {
+    Empty(String),
+    OutOfBounds(String),
+    Overlap(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionState {
+    Locked,
+    Active,
+    Cleared,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegionStatus {
+    pub name: String,
+    pub state: RegionState,
+}
+
+impl Minesweeper {
+    // Divide the board into regions which unlock in the given order
+    pub fn set_regions(&mut self, regions: Vec<Region>) -> Result<(), RegionError> {
+        for (index, region) in regions.iter().enumerate() {
+            if region.width == 0 || region.height == 0 {
+                return Err(RegionError::Empty(region.name.clone()));
+            }
+            if region.x as u32 + region.width as u32 > self.width() as u32
+                || region.y as u32 + region.height as u32 > self.height() as u32
+            {
+                return Err(RegionError::OutOfBounds(region.name.clone()));
+            }
+            if let Some(other) = regions[..index].iter().find(|other| other.overlaps(region)) {
+                return Err(RegionError::Overlap(
+                    other.name.clone(),
+                    region.name.clone(),
+                ));
+            }
+        }
+
+        self.regions = regions;
+        self.update_unlocked_regions();
+        Ok(())
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    pub fn region_statuses(&self) -> Vec<RegionStatus> {
+        self.regions
+            .iter()
+            .enumerate()
+            .map(|(index, region)| RegionStatus {
+                name: region.name.clone(),
+                state: if self.is_region_cleared(region) {
+                    RegionState::Cleared
+                } else if index < self.unlocked_regions {
+                    RegionState::Active
+                } else {
+                    RegionState::Locked
+                },
+            })
+            .collect()
+    }
+
+    pub(crate) fn is_locked(&self, position: Position) -> bool {
+        self.regions
+            .iter()
+            .position(|region| region.contains(position))
+            .is_some_and(|index| index >= self.unlocked_regions)
+    }
+
+    // Recount the playable regions: the cleared ones and the first one which isn't.
+    // Called after every action, so a cascade never runs into a region it unlocks
+    pub(crate) fn update_unlocked_regions(&mut self) {
+        self.unlocked_regions = self
+            .regions
+            .iter()
+            .position(|region| !self.is_region_cleared(region))
+            .map_or(self.regions.len(), |index| index + 1);
+    }
+
+    fn is_region_cleared(&self, region: &Region) -> bool {
+        region.positions().all(|position| {
+            self.mines.contains(&position) || self.open_positions.contains(&position)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(name: &str, x: u16, y: u16, width: u16, height: u16) -> Region {
+        Region {
+            name: name.to_string(),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_set_regions() {
+        let mut minesweeper = crate::board!["....", "...."];
+        assert_eq!(
+            minesweeper.set_regions(vec![region("a", 0, 0, 0, 2)]),
+            Err(RegionError::Empty("a".to_string()))
+        );
+        assert_eq!(
+            minesweeper.set_regions(vec![region("a", 2, 0, 3, 2)]),
+            Err(RegionError::OutOfBounds("a".to_string()))
+        );
+        assert_eq!(
+            minesweeper.set_regions(vec![region("a", 0, 0, 2, 2), region("b", 1, 1, 2, 1)]),
+            Err(RegionError::Overlap("a".to_string(), "b".to_string()))
+        );
+        assert!(
+            minesweeper.regions().is_empty(),
+            "Invalid regions aren't set"
+        );
+    }
+
+    #[test]
+    fn test_progressive_reveal() {
+        //     . * | . .
+        //     . . | . *
+        let mut minesweeper = crate::board![".*..", "...*"];
+        minesweeper
+            .set_regions(vec![region("west", 0, 0, 2, 2), region("east", 2, 0, 2, 2)])
+            .unwrap();
+        let states = |minesweeper: &Minesweeper| -> Vec<RegionState> {
+            minesweeper
+                .region_statuses()
+                .into_iter()
+                .map(|status| status.state)
+                .collect()
+        };
+        assert_eq!(
+            states(&minesweeper),
+            vec![RegionState::Active, RegionState::Locked]
+        );
+
+        // ================================================
+        // Test that a locked region can't be played
+        minesweeper.open((2, 0));
+        assert!(minesweeper.open_positions.is_empty(), "East is locked");
+        assert!(minesweeper.toggle_flag((3, 1)).is_none(), "East is locked");
+
+        // ================================================
+        // Test that clearing a region unlocks the next one
+        minesweeper.open((0, 0)).open((0, 1)).open((1, 1));
+        assert_eq!(
+            states(&minesweeper),
+            vec![RegionState::Cleared, RegionState::Active]
+        );
+        minesweeper.open((2, 0));
+        assert!(minesweeper.open_positions.contains(&(2, 0)));
+    }
+}
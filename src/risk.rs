@@ -0,0 +1,90 @@
+// Coarse risk ratings of the frontier cells, an assist for casual and visually impaired
+// players. Exact odds would play the game for them, so every cell only gets one of three
+// levels. The odds are those of `Minesweeper::mine_odds`, which take the mine total into
+// account, so the ratings agree with the confirmation prompt. A frontier too large to
+// search gets no ratings.
+use crate::model::{Minesweeper, Position};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Risk {
+    // A mine in less than a third of the layouts
+    Low,
+    Medium,
+    // A mine in more than two thirds of the layouts
+    High,
+}
+
+impl Risk {
+    fn new(odds: f64) -> Self {
+        if 3.0 * odds < 1.0 {
+            Risk::Low
+        } else if 3.0 * odds > 2.0 {
+            Risk::High
+        } else {
+            Risk::Medium
+        }
+    }
+}
+
+impl Minesweeper {
+    // Rows of the risk of every frontier cell, None for the other cells
+    pub fn risk_map(&self) -> Vec<Vec<Option<Risk>>> {
+        let odds = self.all_mine_odds().unwrap_or_default();
+        let risk = |position: Position| {
+            self.is_frontier(position)
+                .then(|| odds.get(&position).copied().map(Risk::new))
+                .flatten()
+        };
+
+        (0..self.height())
+            .map(|y| (0..self.width()).map(|x| risk((x, y))).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_map() {
+        // ================================================
+        // Test a certain mine and the cells it makes safe
+        //
+        //     * 1
+        //     1 1
+        //     . .
+        let minesweeper = crate::board!["*o", "oo", ".."];
+        assert_eq!(
+            minesweeper.risk_map(),
+            vec![
+                vec![Some(Risk::High), None],
+                vec![None, None],
+                vec![Some(Risk::Low), Some(Risk::Low)],
+            ]
+        );
+
+        // ================================================
+        // Test that the mine total weighs the layouts and the cells off the frontier. The
+        // numbers allow a mine in the middle or mines at both ends, but a mine in the
+        // middle leaves 6 ways to place the other mine in the sea and mines at the ends 1
+        //
+        //     * 1 . 1 * . . . . . .
+        let minesweeper = crate::board!["*o.o*......"];
+        let mut risks = vec![
+            Some(Risk::Low),
+            None,
+            Some(Risk::High),
+            None,
+            Some(Risk::Low),
+        ];
+        risks.resize(11, None);
+        assert_eq!(minesweeper.risk_map(), vec![risks]);
+        assert!(
+            minesweeper.mine_odds((2, 0)).unwrap() > 0.5,
+            "Confirmed like a high risk"
+        );
+    }
+}
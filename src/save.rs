@@ -0,0 +1,318 @@
+// Saved games, e.g. to restore a game from localStorage after a page refresh. A save is
+// JSON holding the layout, what the player did and the settings which change the rules.
+// The layout is in plain form, so saves must stay out of sight of the player. The undo
+// history, the theme and pending feedback events aren't saved. The time played is saved,
+// so a loaded game counts on from it.
+use crate::{
+    annotation::Annotation,
+    audit::InvariantViolation,
+    clock::{SharedClock, SystemClock},
+    confirmation::ConfirmationPolicy,
+    model::{Minesweeper, Position},
+    region::{Region, RegionError},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+// Bumped when a change breaks older saves, which are then rejected. Fields added with a
+// default keep older saves loadable and don't bump it
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SaveData {
+    version: u32,
+    width: u16,
+    height: u16,
+    mines: Vec<Position>,
+    // Open cells in opening order with the move which opened them
+    opened: Vec<(Position, u32)>,
+    flagged: Vec<Position>,
+    game_over: bool,
+    moves: u32,
+    blind_window: Option<u32>,
+    flag_mode: bool,
+    deductions: u32,
+    guesses: u32,
+    probes_left: u32,
+    probes_used: u32,
+    regions: Vec<Region>,
+    commitment_nonce: Option<String>,
+    safe_first_click: bool,
+    seed: Option<u64>,
+    annotations: Vec<Annotation>,
+    // Added after the first saves. The defaults are the rules those saves were played
+    // with: no flag cap, no confirmations, no question marks and no time
+    #[serde(default)]
+    flag_cap: bool,
+    #[serde(default)]
+    confirmation_policy: ConfirmationPolicy,
+    #[serde(default)]
+    questioned: Vec<Position>,
+    #[serde(default)]
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    Json(String),
+    UnsupportedVersion(u32),
+    InvalidParameters,
+    InvalidRegions(RegionError),
+    InvalidAnnotation(Position),
+    // An open cell listed twice or opened at a move after the last one
+    InvalidOpenCell(Position),
+    Inconsistent(Vec<InvariantViolation>),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Json(error) => write!(f, "invalid JSON: {error}"),
+            LoadError::UnsupportedVersion(version) => {
+                write!(f, "unsupported save version {version}")
+            }
+            LoadError::InvalidParameters => write!(f, "invalid board parameters"),
+            LoadError::InvalidRegions(error) => write!(f, "invalid regions: {error:?}"),
+            LoadError::InvalidAnnotation(position) => {
+                write!(f, "invalid annotation at {position:?}")
+            }
+            LoadError::InvalidOpenCell(position) => write!(f, "invalid open cell {position:?}"),
+            LoadError::Inconsistent(violations) => write!(f, "inconsistent game: {violations:?}"),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl Minesweeper {
+    pub fn serialize(&self) -> String {
+        let mut opened: Vec<(Position, (u32, u32))> = self
+            .opened_at
+            .iter()
+            .map(|(&position, &opened_at)| (position, opened_at))
+            .collect();
+        opened.sort_by_key(|&(_, (_, ordinal))| ordinal);
+        let mut mines: Vec<Position> = self.mines.iter().collect();
+        mines.sort();
+        let mut flagged: Vec<Position> = self.flagged_positions.iter().copied().collect();
+        flagged.sort();
+        let mut questioned: Vec<Position> = self.questioned_positions.iter().copied().collect();
+        questioned.sort();
+
+        let data = SaveData {
+            version: SAVE_VERSION,
+            width: self.width(),
+            height: self.height(),
+            mines,
+            opened: opened
+                .into_iter()
+                .map(|(position, (moves, _))| (position, moves))
+                .collect(),
+            flagged,
+            game_over: self.game_over,
+            moves: self.moves,
+            blind_window: self.blind_mode(),
+            flag_mode: self.flag_mode,
+            deductions: self.deductions,
+            guesses: self.guesses,
+            probes_left: self.probes_left,
+            probes_used: self.probes_used,
+            regions: self.regions.clone(),
+            commitment_nonce: self.commitment_nonce.clone(),
+            safe_first_click: self.safe_first_click(),
+            seed: self.seed,
+            annotations: self.annotations(),
+            flag_cap: self.flag_cap(),
+            confirmation_policy: self.confirmation_policy(),
+            questioned,
+            elapsed_ms: self.elapsed_ms(),
+        };
+        serde_json::to_string(&data).unwrap_or_default()
+    }
+
+    // Restore a saved game. Saves come from storage the player controls, so everything is
+    // checked before the game is returned
+    pub fn deserialize(save: &str) -> Result<Self, LoadError> {
+        Self::deserialize_with_clock(save, Arc::new(SystemClock::new()))
+    }
+
+    // Like `deserialize`, with the clock which counts on from the time played
+    pub fn deserialize_with_clock(save: &str, clock: SharedClock) -> Result<Self, LoadError> {
+        let data: SaveData =
+            serde_json::from_str(save).map_err(|error| LoadError::Json(error.to_string()))?;
+        if data.version != SAVE_VERSION {
+            return Err(LoadError::UnsupportedVersion(data.version));
+        }
+        let is_on_board = |&(x, y): &Position| x < data.width && y < data.height;
+        let mines_count = u16::try_from(data.mines.len()).unwrap_or(0);
+        if !Minesweeper::are_valid_parameters(data.width, data.height, mines_count)
+            || !data.mines.iter().all(is_on_board)
+        {
+            return Err(LoadError::InvalidParameters);
+        }
+
+        let mut minesweeper = Minesweeper::from_mines(data.width, data.height, data.mines);
+        if minesweeper.mines.len() != mines_count as usize {
+            return Err(LoadError::InvalidParameters);
+        }
+        for (position, moves) in data.opened {
+            if moves > data.moves || !minesweeper.open_positions.insert(position) {
+                return Err(LoadError::InvalidOpenCell(position));
+            }
+            let ordinal = minesweeper.opened_at.len() as u32;
+            minesweeper.opened_at.insert(position, (moves, ordinal));
+        }
+        minesweeper.flagged_positions.extend(data.flagged);
+        minesweeper.questioned_positions.extend(data.questioned);
+        minesweeper.game_over = data.game_over;
+        minesweeper.moves = data.moves;
+        minesweeper.set_blind_mode(data.blind_window);
+        minesweeper.flag_mode = data.flag_mode;
+        minesweeper.deductions = data.deductions;
+        minesweeper.guesses = data.guesses;
+        minesweeper.probes_left = data.probes_left;
+        minesweeper.probes_used = data.probes_used;
+        minesweeper.commitment_nonce = data.commitment_nonce;
+        minesweeper.set_safe_first_click(data.safe_first_click);
+        minesweeper.seed = data.seed;
+        minesweeper.set_flag_cap(data.flag_cap);
+        minesweeper.set_confirmation_policy(data.confirmation_policy);
+        minesweeper
+            .set_regions(data.regions)
+            .map_err(LoadError::InvalidRegions)?;
+        for annotation in data.annotations {
+            if !minesweeper.annotate(annotation.position, &annotation.tag) {
+                return Err(LoadError::InvalidAnnotation(annotation.position));
+            }
+        }
+
+        minesweeper.audit().map_err(LoadError::Inconsistent)?;
+        minesweeper.set_clock(clock);
+        minesweeper.restore_timer(data.elapsed_ms);
+        Ok(minesweeper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clock::MockClock,
+        model::{CellState, GameStatus},
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let clock = Arc::new(MockClock::new(0));
+        let mut minesweeper = Minesweeper::new_with_seed(8, 8, 10, 3);
+        minesweeper.set_clock(clock.clone());
+        minesweeper.set_blind_mode(Some(2));
+        minesweeper.set_flag_cap(true);
+        minesweeper.set_confirmation_policy(ConfirmationPolicy::Strict);
+        minesweeper.open((4, 4));
+        clock.advance(12_345);
+        let mine = minesweeper.mines.iter().next().unwrap();
+        minesweeper.toggle_flag(mine);
+        let hidden = (0..8)
+            .map(|x| (x, 0))
+            .find(|&position| minesweeper.cell_state(position) == CellState::Hidden)
+            .unwrap();
+        minesweeper.cycle_mark(hidden);
+        minesweeper.cycle_mark(hidden);
+        minesweeper.annotate((0, 0), "red");
+        minesweeper.commit();
+
+        // ================================================
+        // Test that the loaded game is the same game
+        let save = minesweeper.serialize();
+        let mut loaded = Minesweeper::deserialize_with_clock(&save, clock.clone()).unwrap();
+        assert_eq!(loaded.serialize(), save, "Same save");
+        assert_eq!(loaded.to_string(), minesweeper.to_string(), "Same board");
+        assert_eq!(loaded.seed(), Some(3));
+        assert!(loaded.flag_cap());
+        assert_eq!(loaded.cell_state(hidden), CellState::Questioned);
+        assert_eq!(loaded.confirmation_policy(), ConfirmationPolicy::Strict);
+        assert_eq!(loaded.annotation((0, 0)), Some("red"));
+        assert_eq!(loaded.open_ordinal((4, 4)), Some(0));
+        assert_eq!(loaded.commit(), minesweeper.commit(), "Same commitment");
+        assert_eq!(loaded.elapsed_ms(), 12_345, "Time played");
+
+        // ================================================
+        // Test that the loaded game goes on like the saved one
+        loaded.open(mine);
+        assert_eq!(loaded.status(), GameStatus::InProgress, "Flagged");
+        loaded.toggle_flag(mine);
+        loaded.open(mine);
+        assert_eq!(loaded.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn test_deserialize_without_flag_cap() {
+        let save = crate::board!["*.", ".."].serialize();
+        let save = save.replace(r#","flag_cap":false"#, "");
+        assert!(!save.contains("flag_cap"));
+        assert!(
+            !Minesweeper::deserialize(&save).unwrap().flag_cap(),
+            "Older save"
+        );
+
+        let save = save.replace(r#","elapsed_ms":0"#, "");
+        assert!(!save.contains("elapsed_ms"));
+        assert_eq!(
+            Minesweeper::deserialize(&save).unwrap().elapsed_ms(),
+            0,
+            "Saved without the time"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        let save = crate::board!["*.", ".."].serialize();
+        let edit = |from: &str, to: &str| Minesweeper::deserialize(&save.replace(from, to));
+
+        assert!(matches!(
+            Minesweeper::deserialize("{"),
+            Err(LoadError::Json(_))
+        ));
+        assert_eq!(
+            edit(r#""version":1"#, r#""version":0"#).unwrap_err(),
+            LoadError::UnsupportedVersion(0)
+        );
+        assert_eq!(
+            edit(r#""mines":[[0,0]]"#, r#""mines":[[2,0]]"#).unwrap_err(),
+            LoadError::InvalidParameters,
+            "Mine outside the board"
+        );
+        assert_eq!(
+            edit(r#""mines":[[0,0]]"#, r#""mines":[[0,0],[0,0]]"#).unwrap_err(),
+            LoadError::InvalidParameters,
+            "Duplicate mines"
+        );
+        assert_eq!(
+            edit(r#""opened":[]"#, r#""opened":[[[0,0],0]]"#).unwrap_err(),
+            LoadError::Inconsistent(vec![InvariantViolation::OpenMineInProgress((0, 0))])
+        );
+        assert_eq!(
+            edit(r#""opened":[]"#, r#""opened":[[[1,1],7]]"#).unwrap_err(),
+            LoadError::InvalidOpenCell((1, 1)),
+            "Opened after the last move"
+        );
+        assert_eq!(
+            edit(r#""opened":[]"#, r#""opened":[[[1,1],0],[[1,1],0]]"#).unwrap_err(),
+            LoadError::InvalidOpenCell((1, 1)),
+            "Opened twice"
+        );
+        assert_eq!(
+            edit(
+                r#""annotations":[]"#,
+                r#""annotations":[{"position":[5,5],"tag":"red"}]"#
+            )
+            .unwrap_err(),
+            LoadError::InvalidAnnotation((5, 5))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+// Human-friendly seed phrases like `brave-otter-42`, so boards can be shared verbally or
+// in chat. A phrase is a bijection with u64: the adjective holds the lowest 6 bits of
+// the seed, the noun the next 6 bits and the number the remaining 52 bits, so small
+// seeds such as dates give short phrases.
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+const WORD_BITS: u32 = 6;
+const WORD_MASK: u64 = (1 << WORD_BITS) - 1;
+
+const ADJECTIVES: [&str; 64] = [
+    "brave",
+    "calm",
+    "clever",
+    "cosmic",
+    "crisp",
+    "curious",
+    "daring",
+    "dusty",
+    "eager",
+    "early",
+    "fancy",
+    "fearless",
+    "fierce",
+    "fluffy",
+    "gentle",
+    "giant",
+    "glad",
+    "golden",
+    "grand",
+    "happy",
+    "hidden",
+    "humble",
+    "icy",
+    "jolly",
+    "keen",
+    "kind",
+    "lively",
+    "lucky",
+    "mellow",
+    "merry",
+    "mighty",
+    "misty",
+    "noble",
+    "odd",
+    "patient",
+    "plucky",
+    "polite",
+    "proud",
+    "quick",
+    "quiet",
+    "rapid",
+    "rusty",
+    "shiny",
+    "shy",
+    "silent",
+    "silly",
+    "sleepy",
+    "smooth",
+    "snowy",
+    "solar",
+    "speedy",
+    "spicy",
+    "steady",
+    "sunny",
+    "swift",
+    "tidy",
+    "tiny",
+    "vivid",
+    "wandering",
+    "warm",
+    "wild",
+    "wise",
+    "witty",
+    "zesty",
+];
+
+const NOUNS: [&str; 64] = [
+    "otter", "badger", "beaver", "bison", "camel", "cheetah", "cobra", "condor", "coyote", "crane",
+    "dingo", "dolphin", "eagle", "falcon", "ferret", "finch", "fox", "gecko", "gopher", "hare",
+    "hawk", "hedgehog", "heron", "iguana", "jackal", "jaguar", "koala", "lemur", "leopard", "lion",
+    "llama", "lynx", "magpie", "marmot", "moose", "newt", "ocelot", "orca", "osprey", "owl",
+    "panda", "panther", "parrot", "pelican", "penguin", "puffin", "quail", "rabbit", "raven",
+    "robin", "salmon", "seal", "shark", "sloth", "sparrow", "squid", "swan", "tiger", "toucan",
+    "turtle", "walrus", "wombat", "yak", "zebra",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedPhraseError {
+    // Not three parts separated by dashes
+    InvalidFormat,
+    UnknownWord(String),
+    InvalidNumber(String),
+}
+
+impl Display for SeedPhraseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeedPhraseError::InvalidFormat => write!(f, "expected adjective-noun-number"),
+            SeedPhraseError::UnknownWord(word) => write!(f, "unknown word {word:?}"),
+            SeedPhraseError::InvalidNumber(number) => write!(f, "invalid number {number:?}"),
+        }
+    }
+}
+
+impl Error for SeedPhraseError {}
+
+pub fn encode_seed(seed: u64) -> String {
+    let adjective = ADJECTIVES[(seed & WORD_MASK) as usize];
+    let noun = NOUNS[(seed >> WORD_BITS & WORD_MASK) as usize];
+    let number = seed >> (2 * WORD_BITS);
+    format!("{adjective}-{noun}-{number}")
+}
+
+// Decode a phrase. Letter case and surrounding whitespace are ignored
+pub fn decode_seed(phrase: &str) -> Result<u64, SeedPhraseError> {
+    let phrase = phrase.trim().to_lowercase();
+    let [adjective, noun, number] = phrase.split('-').collect::<Vec<_>>()[..] else {
+        return Err(SeedPhraseError::InvalidFormat);
+    };
+
+    let index = |words: &[&str], word: &str| {
+        words
+            .iter()
+            .position(|candidate| *candidate == word)
+            .map(|index| index as u64)
+            .ok_or_else(|| SeedPhraseError::UnknownWord(word.to_string()))
+    };
+    let adjective = index(&ADJECTIVES, adjective)?;
+    let noun = index(&NOUNS, noun)?;
+    let number = number
+        .parse::<u64>()
+        .ok()
+        .filter(|number| number >> (64 - 2 * WORD_BITS) == 0)
+        .ok_or_else(|| SeedPhraseError::InvalidNumber(number.to_string()))?;
+
+    Ok(number << (2 * WORD_BITS) | noun << WORD_BITS | adjective)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_seed() {
+        assert_eq!(encode_seed(0), "brave-otter-0");
+        assert_eq!(encode_seed(20261016), "keen-moose-4946");
+        assert_eq!(encode_seed(u64::MAX), "zesty-zebra-4503599627370495");
+    }
+
+    #[test]
+    fn test_decode_seed() {
+        // ================================================
+        // Test round trips
+        [0, 1, 63, 64, 4095, 4096, 20261016, u64::MAX]
+            .into_iter()
+            .for_each(|seed| assert_eq!(decode_seed(&encode_seed(seed)), Ok(seed), "{seed}"));
+        assert_eq!(
+            decode_seed(" Keen-Moose-4946\n"),
+            Ok(20261016),
+            "Case and whitespace"
+        );
+
+        // ================================================
+        // Test invalid phrases
+        assert_eq!(
+            decode_seed("keen-moose"),
+            Err(SeedPhraseError::InvalidFormat)
+        );
+        assert_eq!(
+            decode_seed("keen-moose-1-2"),
+            Err(SeedPhraseError::InvalidFormat)
+        );
+        assert_eq!(
+            decode_seed("swift-unicorn-1"),
+            Err(SeedPhraseError::UnknownWord("unicorn".to_string()))
+        );
+        assert_eq!(
+            decode_seed("keen-moose-x"),
+            Err(SeedPhraseError::InvalidNumber("x".to_string()))
+        );
+        assert_eq!(
+            decode_seed("keen-moose-4503599627370496"),
+            Err(SeedPhraseError::InvalidNumber(
+                "4503599627370496".to_string()
+            )),
+            "Number too big"
+        );
+    }
+
+    #[test]
+    fn test_words_are_distinct() {
+        let mut words: Vec<&str> = ADJECTIVES.iter().chain(NOUNS.iter()).copied().collect();
+        words.sort();
+        words.dedup();
+        assert_eq!(words.len(), 128, "Every word is unique");
+        assert!(
+            words.iter().all(|word| !word.contains('-')),
+            "No dashes in words"
+        );
+    }
+}
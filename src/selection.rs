@@ -0,0 +1,173 @@
+// Opening a dragged rectangle at once, for touch UIs where clearing a solved area cell
+// by cell is tedious. The whole rectangle is a single move: one diff, one history entry
+// and one undo.
+use crate::{
+    action::Action,
+    model::{Minesweeper, Position},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    // The positions of the rectangle on a board of the given size in row-major order.
+    // The part outside the board is cut off
+    pub fn positions(&self, width: u16, height: u16) -> impl Iterator<Item = Position> {
+        let Rect { x, y, .. } = *self;
+        let right = (x as u32 + self.width as u32).min(width as u32) as u16;
+        let bottom = (y as u32 + self.height as u32).min(height as u32) as u16;
+        (y..bottom).flat_map(move |j| (x..right).map(move |i| (i, j)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub enum OpenRegionError {
+    // Every cell of the rectangle is open, flagged, locked or outside the board, or the
+    // game is over
+    NothingToOpen,
+    // The numbers prove that the position has a mine, so it should have been flagged
+    ProvenMine { position: Position },
+}
+
+impl Minesweeper {
+    // Open every hidden, unflagged cell of the rectangle as a single move. The rectangle
+    // is refused as a whole if the numbers prove that one of its cells is a mine, so a
+    // sloppy drag over a solved area can't lose the game. Cells without proof are opened
+    // like clicks, in row-major order, and may still explode
+    pub fn open_region(&mut self, rect: Rect) -> Result<(), OpenRegionError> {
+        let targets: Vec<Position> = rect
+            .positions(self.width(), self.height())
+            .filter(|position| self.can_be_opened(position))
+            .collect();
+        let Some(&first) = targets.first() else {
+            return Err(OpenRegionError::NothingToOpen);
+        };
+        let partition = self.partition();
+        let proven_mine = partition
+            .components
+            .iter()
+            .flat_map(|component| &component.mines)
+            .find(|position| targets.contains(position));
+        if let Some(&position) = proven_mine {
+            return Err(OpenRegionError::ProvenMine { position });
+        }
+
+        self.record(Action::OpenRegion(rect), |minesweeper| {
+            if minesweeper.safe_first_click() && minesweeper.layout_is_movable() {
+                minesweeper.clear_first_click(first);
+            }
+            let proven_safe = minesweeper.provably_safe_cells(&targets);
+            let open_before = minesweeper.open_positions.len();
+            let mut exploded = None;
+            for target in targets {
+                // Cells opened by an earlier cascade are skipped, the others are judged
+                // on the board as it was before the move
+                if minesweeper.can_be_opened(&target) {
+                    minesweeper.judge(target, &proven_safe);
+                    minesweeper.open_cascade(target);
+                    if minesweeper.game_over {
+                        exploded = Some(target);
+                    }
+                }
+            }
+            let opened_cells = minesweeper.open_positions.len() - open_before;
+            minesweeper.finish_open(exploded.unwrap_or(first), opened_cells);
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CellState, GameStatus};
+
+    fn rect(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        let positions: Vec<Position> = rect(1, 1, 2, 5).positions(3, 3).collect();
+        assert_eq!(positions, vec![(1, 1), (2, 1), (1, 2), (2, 2)], "Cut off");
+        assert_eq!(rect(0, 0, 0, 3).positions(3, 3).count(), 0, "Empty");
+        assert_eq!(
+            rect(u16::MAX, 0, u16::MAX, 1).positions(3, 3).count(),
+            0,
+            "Outside the board"
+        );
+    }
+
+    #[test]
+    fn test_open_region() {
+        let mut minesweeper = crate::board!["*...", "....", "...*"];
+
+        // ================================================
+        // Test that the rectangle and its cascades are a single move
+        let changes = minesweeper.apply_with_changes(Action::OpenRegion(rect(1, 0, 2, 2)));
+        assert_eq!(changes.len(), 6, "Rectangle and cascade");
+        assert_eq!(minesweeper.moves(), 1, "Single move");
+        assert_eq!(minesweeper.history().len(), 1, "Single history entry");
+        assert_eq!(minesweeper.history()[0].opened().len(), 6);
+        assert_eq!(minesweeper.cell_state((3, 0)), CellState::Open(0));
+
+        minesweeper.undo();
+        assert!(minesweeper.open_positions.is_empty(), "Undone at once");
+        minesweeper.redo();
+        assert_eq!(minesweeper.open_positions.len(), 6, "Redone at once");
+
+        // ================================================
+        // Test that a rectangle without hidden cells is refused
+        assert_eq!(
+            minesweeper.open_region(rect(2, 0, 2, 2)),
+            Err(OpenRegionError::NothingToOpen),
+            "Already open"
+        );
+        assert_eq!(
+            minesweeper.open_region(rect(4, 0, 2, 2)),
+            Err(OpenRegionError::NothingToOpen),
+            "Outside the board"
+        );
+        assert_eq!(minesweeper.moves(), 1, "No move made");
+
+        // ================================================
+        // Test that an unproven mine still explodes
+        let mut lost = crate::board!["*...", "....", "...*"];
+        assert_eq!(lost.open_region(rect(0, 0, 4, 1)), Ok(()));
+        assert_eq!(lost.status(), GameStatus::Lost);
+        assert_eq!(lost.cell_state((0, 0)), CellState::Exploded);
+        assert_eq!(lost.moves(), 1);
+    }
+
+    #[test]
+    fn test_open_region_proven_mine() {
+        let mut minesweeper = crate::board!["*o..", "oo..", "...."];
+
+        // ================================================
+        // Test that a rectangle over a proven mine is refused as a whole
+        assert_eq!(
+            minesweeper.open_region(rect(0, 0, 3, 3)),
+            Err(OpenRegionError::ProvenMine { position: (0, 0) })
+        );
+        assert_eq!(minesweeper.moves(), 0, "Nothing opened");
+
+        // ================================================
+        // Test that flagging the mine makes the rectangle safe
+        minesweeper.toggle_flag((0, 0));
+        assert_eq!(minesweeper.open_region(rect(0, 0, 4, 3)), Ok(()));
+        assert_eq!(minesweeper.status(), GameStatus::Won);
+        assert_eq!(minesweeper.deductions, 1, "Cascades aren't judged");
+    }
+}
@@ -0,0 +1,224 @@
+use crate::model::{CellState, GameStatus, Minesweeper, Position};
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    sync::{Arc, OnceLock},
+};
+
+// Board data shared by a game, its snapshots and its sandboxes. Cloning only bumps a
+// reference count, and the first change to shared data copies it, so the copies never
+// see each other's changes
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Shared<T>(Arc<T>);
+
+impl<T> Shared<T> {
+    // Check if both share the same data
+    pub fn ptr_eq(&self, other: &Shared<T>) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Shared<T>
+where
+    &'a T: IntoIterator,
+{
+    type Item = <&'a T as IntoIterator>::Item;
+    type IntoIter = <&'a T as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.as_ref().into_iter()
+    }
+}
+
+impl<T: Debug> Debug for Shared<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// Immutable copy of the game at one point, without the moves which led there. Taking
+// and cloning a snapshot is cheap since the board data is shared, see `Shared`. The
+// visible cells are only collected when they are first read
+#[derive(Clone)]
+pub struct GameSnapshot {
+    data: Arc<SnapshotData>,
+}
+
+struct SnapshotData {
+    game: Minesweeper,
+    // Cells in row-major order
+    cells: OnceLock<Vec<CellState>>,
+}
+
+impl GameSnapshot {
+    pub fn new(minesweeper: &Minesweeper) -> Self {
+        let mut game = minesweeper.clone();
+        game.clear_history();
+        Self {
+            data: Arc::new(SnapshotData {
+                game,
+                cells: OnceLock::new(),
+            }),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.data.game.width()
+    }
+
+    pub fn height(&self) -> u16 {
+        self.data.game.height()
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.data.game.status()
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.data.game.moves()
+    }
+
+    pub fn remaining_mines(&self) -> i32 {
+        self.data.game.remaining_mines()
+    }
+
+    pub fn cell(&self, (x, y): Position) -> Option<CellState> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        self.cells()
+            .get(y as usize * self.width() as usize + x as usize)
+            .copied()
+    }
+
+    // All cells in row-major order
+    pub fn cells(&self) -> &[CellState] {
+        self.data.cells.get_or_init(|| {
+            let game = &self.data.game;
+            (0..game.height())
+                .flat_map(|y| (0..game.width()).map(move |x| (x, y)))
+                .map(|position| game.cell_state(position))
+                .collect()
+        })
+    }
+
+    // Check if both snapshots share the same board data
+    pub fn ptr_eq(&self, other: &GameSnapshot) -> bool {
+        Arc::ptr_eq(&self.data, &other.data)
+    }
+
+    // A game starting from the snapshot, see `Minesweeper::sandbox`
+    pub fn sandbox(&self) -> Minesweeper {
+        self.data.game.clone()
+    }
+}
+
+// Snapshots are equal if the player sees the same on both
+impl PartialEq for GameSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        (self.width(), self.height(), self.status(), self.moves())
+            == (other.width(), other.height(), other.status(), other.moves())
+            && self.remaining_mines() == other.remaining_mines()
+            && self.cells() == other.cells()
+    }
+}
+
+impl Eq for GameSnapshot {}
+
+impl Debug for GameSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameSnapshot")
+            .field("status", &self.status())
+            .field("moves", &self.moves())
+            .field("cells", &self.cells())
+            .finish()
+    }
+}
+
+impl Minesweeper {
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let mut minesweeper = Minesweeper::new(10, 10, 10);
+        // Remove all mines
+        minesweeper.mines.drain();
+        minesweeper.set_safe_first_click(false);
+        // Insert 1 mine around the cell to prevent opening other cells
+        minesweeper.mines.insert((5, 6));
+        minesweeper.open((5, 5));
+
+        // ================================================
+        // Test that the snapshot captures the visible state
+        let snapshot = minesweeper.snapshot();
+        assert_eq!((snapshot.width(), snapshot.height()), (10, 10));
+        assert_eq!(snapshot.status(), GameStatus::InProgress);
+        assert_eq!(snapshot.moves(), 1);
+        assert_eq!(snapshot.remaining_mines(), 1);
+        assert_eq!(snapshot.cells().len(), 100);
+        assert_eq!(snapshot.cell((5, 5)), Some(CellState::Open(1)));
+        assert_eq!(snapshot.cell((5, 6)), Some(CellState::Hidden));
+        assert_eq!(snapshot.cell((10, 10)), None, "Outside the board");
+
+        // ================================================
+        // Test that the snapshot doesn't change with the game
+        minesweeper.toggle_flag((5, 6));
+        assert_eq!(snapshot.cell((5, 6)), Some(CellState::Hidden));
+        assert_ne!(snapshot, minesweeper.snapshot(), "Game changed");
+
+        // ================================================
+        // Test that clones share the board data
+        let clone = snapshot.clone();
+        assert!(clone.ptr_eq(&snapshot), "Clone shares the data");
+        assert!(!minesweeper.snapshot().ptr_eq(&snapshot));
+        assert_eq!(snapshot.sandbox().moves(), 1, "Game at the snapshot");
+        assert!(!snapshot.sandbox().can_undo(), "Without the history");
+    }
+
+    #[test]
+    fn test_shared() {
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.open((1, 1));
+
+        // ================================================
+        // Test that a copy shares the board data until it changes it
+        let mut copy = minesweeper.clone();
+        assert!(copy.mines.ptr_eq(&minesweeper.mines));
+        copy.toggle_flag((0, 0));
+        assert!(!copy
+            .flagged_positions
+            .ptr_eq(&minesweeper.flagged_positions));
+        assert!(minesweeper.flagged_positions.is_empty(), "Game unchanged");
+        assert!(
+            copy.open_positions.ptr_eq(&minesweeper.open_positions),
+            "Unchanged data still shared"
+        );
+    }
+}
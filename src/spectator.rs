@@ -0,0 +1,92 @@
+// Overlay for spectators and commentators: what the player could know about every
+// unknown cell from the numbers on the board. It is built from the player's view only,
+// so it never reveals a mine the player couldn't deduce. Flags are the player's guesses
+// and count as unknown cells.
+use crate::{
+    hash::HashMap,
+    model::{CellState, Minesweeper, Position},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Knowledge {
+    Safe,
+    Mine,
+    Unknown,
+}
+
+impl Minesweeper {
+    // Rows of the knowledge of every hidden or flagged cell, None for the other cells
+    pub fn spectator_overlay(&self) -> Vec<Vec<Option<Knowledge>>> {
+        let partition = self.partition();
+        let mut known: HashMap<Position, Knowledge> = HashMap::default();
+        for component in &partition.components {
+            component.safe.iter().for_each(|&position| {
+                known.insert(position, Knowledge::Safe);
+            });
+            component.mines.iter().for_each(|&position| {
+                known.insert(position, Knowledge::Mine);
+            });
+        }
+        // The sea is known when the mine total leaves no choice for it
+        let sea = if partition.sea_max_mines == 0 {
+            Knowledge::Safe
+        } else if partition.sea_min_mines == partition.sea.len() {
+            Knowledge::Mine
+        } else {
+            Knowledge::Unknown
+        };
+        partition.sea.iter().for_each(|&position| {
+            known.insert(position, sea);
+        });
+
+        (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| match self.cell_state((x, y)) {
+                        CellState::Hidden | CellState::Flagged | CellState::Questioned => {
+                            Some(known.get(&(x, y)).copied().unwrap_or(Knowledge::Unknown))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spectator_overlay() {
+        // ================================================
+        // Test a mine and safe cells proven by the numbers
+        //
+        //     * 1 . .
+        //     1 1 . .
+        //     . . . .
+        let overlay = crate::board!["*o..", "oo..", "...."].spectator_overlay();
+        assert_eq!(overlay[0][0], Some(Knowledge::Mine));
+        assert_eq!(overlay[0][1], None, "Open cell");
+        assert_eq!(overlay[2][2], Some(Knowledge::Safe), "Frontier cell");
+        assert_eq!(
+            overlay[0][3],
+            Some(Knowledge::Safe),
+            "The only mine is known"
+        );
+
+        // ================================================
+        // Test that the sea is unknown when a mine may be anywhere in it, and that
+        // flags don't count as knowledge
+        let mut minesweeper = crate::board!["*o...", "oo...", ".....", "....*"];
+        minesweeper.toggle_flag((4, 3));
+        let overlay = minesweeper.spectator_overlay();
+        assert_eq!(overlay[0][0], Some(Knowledge::Mine));
+        assert_eq!(overlay[2][2], Some(Knowledge::Safe));
+        assert_eq!(overlay[3][4], Some(Knowledge::Unknown), "Flagged mine");
+        assert_eq!(overlay[0][4], Some(Knowledge::Unknown));
+    }
+}
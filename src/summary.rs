@@ -0,0 +1,247 @@
+// Result of a finished game in a shareable form. Every frontend gets the same card, so
+// shared results look alike wherever they were played. The heatmap shows in which
+// quarter of the solve every cell was opened, from green for the first quarter to red
+// for the last one. The time is added with `ResultSummary::with_time`, e.g. from
+// `Minesweeper::elapsed_ms`.
+use crate::{
+    hash::HashSet,
+    model::{GameStatus, Minesweeper, Position},
+};
+use serde::Serialize;
+
+const HEAT: [&str; 4] = ["🟩", "🟨", "🟧", "🟥"];
+const UNOPENED: &str = "⬛";
+const EXPLOSION: &str = "💥";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResultSummary {
+    pub width: u16,
+    pub height: u16,
+    pub mines: u16,
+    pub status: GameStatus,
+    pub moves: u32,
+    // Minimal number of clicks needed to clear the board
+    pub three_bv: u32,
+    // Rows of the quarter of the solve, 0..=3, in which every cell was opened, or null
+    // for cells that were never opened
+    pub heatmap: Vec<Vec<Option<u8>>>,
+    // The mine which ended a lost game
+    pub exploded: Option<Position>,
+    // Milliseconds played and the 3BV cleared per second, null until the time is added.
+    // Only a won game has a speed, a lost one didn't clear the board
+    pub elapsed_ms: Option<u64>,
+    pub three_bv_per_second: Option<f64>,
+    // The penalty of a casual game, see `Minesweeper::probe`
+    pub probes_used: u32,
+}
+
+impl ResultSummary {
+    pub fn with_time(mut self, elapsed_ms: u64) -> Self {
+        self.elapsed_ms = Some(elapsed_ms);
+        self.three_bv_per_second = (self.status == GameStatus::Won && elapsed_ms > 0)
+            .then(|| self.three_bv as f64 * 1000.0 / elapsed_ms as f64);
+        self
+    }
+
+    // Compact text to share, e.g.
+    //
+    //     Minesweeper 3x2 💣1
+    //     ✅ Won · 2 moves · 3BV 2 · 12.5 s · 0.16 3BV/s
+    //     ⬛🟩🟩
+    //     🟥🟨🟧
+    pub fn card(&self) -> String {
+        let outcome = match self.status {
+            GameStatus::Won => "✅ Won",
+            GameStatus::Lost => "❌ Lost",
+            GameStatus::InProgress => "⏳ In progress",
+        };
+        let mut card = format!(
+            "Minesweeper {}x{} 💣{}\n{outcome} · {} moves · 3BV {}",
+            self.width, self.height, self.mines, self.moves, self.three_bv
+        );
+        if let Some(elapsed_ms) = self.elapsed_ms {
+            card.push_str(&format!(" · {:.1} s", elapsed_ms as f64 / 1000.0));
+        }
+        if let Some(speed) = self.three_bv_per_second {
+            card.push_str(&format!(" · {speed:.2} 3BV/s"));
+        }
+        if self.probes_used > 0 {
+            card.push_str(&format!(" · {} probes", self.probes_used));
+        }
+        card.push('\n');
+        for (y, row) in self.heatmap.iter().enumerate() {
+            for (x, quarter) in row.iter().enumerate() {
+                let glyph = match quarter {
+                    _ if self.exploded == Some((x as u16, y as u16)) => EXPLOSION,
+                    Some(quarter) => HEAT[*quarter as usize],
+                    None => UNOPENED,
+                };
+                card.push_str(glyph);
+            }
+            card.push('\n');
+        }
+        card
+    }
+}
+
+impl Minesweeper {
+    // Summary of the game, available once it's over
+    pub fn result_summary(&self) -> Option<ResultSummary> {
+        let status = self.status();
+        if status == GameStatus::InProgress {
+            return None;
+        }
+
+        let opened = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .filter_map(|position| self.open_ordinal(position))
+            .count() as u32;
+        let heatmap = (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| {
+                        self.open_ordinal((x, y))
+                            .map(|ordinal| (ordinal * 4 / opened.max(1)) as u8)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(ResultSummary {
+            width: self.width(),
+            height: self.height(),
+            mines: self.mines.len() as u16,
+            status,
+            moves: self.moves(),
+            three_bv: self.three_bv(),
+            heatmap,
+            exploded: self
+                .mines
+                .iter()
+                .find(|position| self.open_positions.contains(position)),
+            elapsed_ms: None,
+            three_bv_per_second: None,
+            probes_used: self.probes_used(),
+        })
+    }
+
+    // Board Benchmark Value: the number of clicks needed to clear the board without
+    // flags. Every opening takes one click and so does every number outside openings
+    pub fn three_bv(&self) -> u32 {
+        let mut cleared: HashSet<Position> = HashSet::default();
+        let mut clicks = 0;
+        let positions = (0..self.height()).flat_map(|y| (0..self.width()).map(move |x| (x, y)));
+
+        for position in positions.clone() {
+            if cleared.contains(&position)
+                || self.mines.contains(&position)
+                || self.mines_around(position) != 0
+            {
+                continue;
+            }
+            clicks += 1;
+            cleared.insert(position);
+            let mut stack = vec![position];
+            while let Some(zero) = stack.pop() {
+                for neighbour in self.neighbours(zero) {
+                    if !self.mines.contains(&neighbour)
+                        && cleared.insert(neighbour)
+                        && self.mines_around(neighbour) == 0
+                    {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        clicks
+            + positions
+                .filter(|position| !cleared.contains(position) && !self.mines.contains(position))
+                .count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_three_bv() {
+        // One opening on the right and the 1 below the mine
+        //
+        //     * 1 .
+        //     1 1 .
+        assert_eq!(crate::board!["*..", "..."].three_bv(), 2);
+        assert_eq!(crate::board!["...", "..."].three_bv(), 1, "One opening");
+        // Every safe cell is a number
+        assert_eq!(crate::board!["*.*", "*.*"].three_bv(), 2);
+    }
+
+    #[test]
+    fn test_result_summary() {
+        let mut minesweeper = crate::board!["*..", "..."];
+        assert_eq!(minesweeper.result_summary(), None, "Game in progress");
+
+        // ================================================
+        // Test a won game: the cascade opens 4 cells, then the last number is opened
+        minesweeper.open((2, 0)).open((0, 1));
+        let summary = minesweeper.result_summary().unwrap();
+        assert_eq!(summary.status, GameStatus::Won);
+        assert_eq!((summary.moves, summary.three_bv), (2, 2));
+        assert_eq!(
+            summary.heatmap,
+            vec![
+                vec![None, Some(0), Some(0)],
+                vec![Some(3), Some(1), Some(2)]
+            ]
+        );
+        assert_eq!(summary.exploded, None);
+        assert_eq!(
+            summary.card(),
+            "Minesweeper 3x2 💣1\n✅ Won · 2 moves · 3BV 2\n⬛🟩🟩\n🟥🟨🟧\n"
+        );
+
+        // ================================================
+        // Test the time and the speed of a won game
+        let summary = summary.with_time(12_500);
+        assert_eq!(summary.elapsed_ms, Some(12_500));
+        assert_eq!(summary.three_bv_per_second, Some(0.16));
+        assert_eq!(
+            summary.card(),
+            "Minesweeper 3x2 💣1\n✅ Won · 2 moves · 3BV 2 · 12.5 s · 0.16 3BV/s\n⬛🟩🟩\n🟥🟨🟧\n"
+        );
+        assert_eq!(
+            summary.with_time(0).three_bv_per_second,
+            None,
+            "No time counted"
+        );
+
+        // ================================================
+        // Test that the explosion is marked on a lost game
+        let mut minesweeper = crate::board!["*..", "..."];
+        minesweeper.open((0, 0));
+        let summary = minesweeper.result_summary().unwrap();
+        assert_eq!(summary.exploded, Some((0, 0)));
+        assert_eq!(
+            summary.card(),
+            "Minesweeper 3x2 💣1\n❌ Lost · 1 moves · 3BV 2\n💥⬛⬛\n⬛⬛⬛\n"
+        );
+        let summary = summary.with_time(500);
+        assert_eq!(summary.three_bv_per_second, None, "Lost");
+        assert!(summary
+            .card()
+            .contains("❌ Lost · 1 moves · 3BV 2 · 0.5 s\n"));
+
+        // ================================================
+        // Test that the probes spent are shown as the penalty
+        let mut minesweeper = crate::board!["*..", "..."];
+        minesweeper.set_probes(1);
+        minesweeper.probe((0, 1));
+        minesweeper.open((2, 0));
+        let summary = minesweeper.result_summary().unwrap();
+        assert_eq!(summary.probes_used, 1);
+        assert!(summary
+            .card()
+            .contains("✅ Won · 2 moves · 3BV 2 · 1 probes\n"));
+    }
+}
@@ -0,0 +1,186 @@
+// Helpers for layout-based tests, available with the `test-util` feature.
+//
+// A layout is a list of rows of equal length with one character per cell:
+//
+//     .    hidden cell without a mine
+//     *    hidden mine
+//     o    open cell without a mine
+//     f    flagged cell without a mine
+//     F    flagged mine
+//
+// Fixtures bundle layouts with the expected output. A fixture starts with a
+// `--- <name>` line followed by the layout rows, a `===` line and the expected
+// output lines. Empty lines between fixtures are ignored.
+use crate::model::Minesweeper;
+
+// Build a game from layout rows, e.g. `board!["..*.", ".*.."]`
+#[macro_export]
+macro_rules! board {
+    ($($row:expr),+ $(,)?) => {
+        $crate::test_util::from_layout(&[$($row),+])
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    Empty,
+    UnevenRows { y: usize },
+    InvalidCell { x: usize, y: usize, cell: char },
+}
+
+pub fn parse_layout(rows: &[&str]) -> Result<Minesweeper, LayoutError> {
+    let width = rows.first().ok_or(LayoutError::Empty)?.chars().count();
+    if width == 0 {
+        return Err(LayoutError::Empty);
+    }
+
+    let mut mines = Vec::new();
+    let mut open = Vec::new();
+    let mut flagged = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() != width {
+            return Err(LayoutError::UnevenRows { y });
+        }
+        for (x, cell) in row.chars().enumerate() {
+            let position = (x as u16, y as u16);
+            match cell {
+                '.' => {}
+                '*' => mines.push(position),
+                'o' => open.push(position),
+                'f' => flagged.push(position),
+                'F' => {
+                    mines.push(position);
+                    flagged.push(position);
+                }
+                _ => return Err(LayoutError::InvalidCell { x, y, cell }),
+            }
+        }
+    }
+
+    let mut minesweeper = Minesweeper::from_mines(width as u16, rows.len() as u16, mines);
+    // Open the cells one by one without cascading, so the layout is exactly as written
+    minesweeper.open_positions.extend(open);
+    minesweeper.flagged_positions.extend(flagged);
+    Ok(minesweeper)
+}
+
+// Like `parse_layout`, but panics on invalid layouts
+pub fn from_layout(rows: &[&str]) -> Minesweeper {
+    parse_layout(rows).unwrap_or_else(|error| panic!("Invalid layout: {error:?}"))
+}
+
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: String,
+    pub game: Minesweeper,
+    pub expected: Vec<String>,
+}
+
+pub fn load_fixtures(text: &str) -> Vec<Fixture> {
+    let mut fixtures = Vec::new();
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .peekable();
+
+    while let Some(header) = lines.next() {
+        let name = header
+            .strip_prefix("--- ")
+            .unwrap_or_else(|| panic!("Expected a fixture header, got {header:?}"))
+            .trim()
+            .to_string();
+
+        let layout: Vec<&str> = lines.by_ref().take_while(|line| *line != "===").collect();
+        let mut expected = Vec::new();
+        while let Some(line) = lines.next_if(|line| !line.starts_with("--- ")) {
+            expected.push(line.to_string());
+        }
+
+        fixtures.push(Fixture {
+            game: from_layout(&layout),
+            name,
+            expected,
+        });
+    }
+
+    fixtures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_board() {
+        let minesweeper = board!["..*.", ".*o.", "fF.."];
+        assert_eq!((minesweeper.width(), minesweeper.height()), (4, 3));
+        assert_eq!(minesweeper.mines.len(), 3, "3 mines");
+        assert!(minesweeper.mines.contains(&(2, 0)));
+        assert!(minesweeper.mines.contains(&(1, 1)));
+        assert!(minesweeper.mines.contains(&(1, 2)));
+        assert_eq!(minesweeper.open_positions.len(), 1, "1 open cell");
+        assert!(minesweeper.open_positions.contains(&(2, 1)));
+        assert_eq!(minesweeper.flagged_positions.len(), 2, "2 flags");
+    }
+
+    #[test]
+    fn test_parse_layout() {
+        assert_eq!(parse_layout(&[]).unwrap_err(), LayoutError::Empty);
+        assert_eq!(parse_layout(&[""]).unwrap_err(), LayoutError::Empty);
+        assert_eq!(
+            parse_layout(&["..", "..."]).unwrap_err(),
+            LayoutError::UnevenRows { y: 1 }
+        );
+        assert_eq!(
+            parse_layout(&["..", ".?"]).unwrap_err(),
+            LayoutError::InvalidCell {
+                x: 1,
+                y: 1,
+                cell: '?'
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic = "Invalid layout"]
+    fn test_from_layout_invalid() {
+        from_layout(&["..", "."]);
+    }
+
+    const FIXTURES: &str = "
+--- corner mine
+*..
+...
+===
+# # #
+# # #
+
+--- open numbers
+*o.
+oo.
+===
+# 1 #
+1 1 #
+";
+
+    #[test]
+    fn test_load_fixtures() {
+        let fixtures = load_fixtures(FIXTURES);
+        assert_eq!(fixtures.len(), 2, "2 fixtures");
+        assert_eq!(fixtures[0].name, "corner mine");
+        assert_eq!(fixtures[1].game.open_positions.len(), 3);
+
+        // Check the expected render of every fixture with the ASCII theme
+        fixtures.into_iter().for_each(|mut fixture| {
+            fixture.game.set_theme(Theme::ASCII);
+            let rendered: Vec<String> = fixture
+                .game
+                .to_string()
+                .lines()
+                .map(|line| line.trim_end().to_string())
+                .collect();
+            assert_eq!(rendered, fixture.expected, "Fixture {}", fixture.name);
+        });
+    }
+}
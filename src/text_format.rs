@@ -0,0 +1,248 @@
+// Version 2 of the text game state format.
+//
+// The first line is a header with fields separated by semicolons:
+//
+//     v2;<width>;<height>;<status>
+//
+// where status is one of `in_progress`, `won` or `lost`. It is followed by
+// `height` lines of exactly `width` characters, one character per cell:
+//
+//     #        hidden cell
+//     F        flagged cell
+//     ?        hidden cell with a question mark
+//     0..=8    open cell with the number of mines around it
+//     _        open cell with a masked number (blind mode)
+//     *        mine revealed at the end of the game
+//     X        exploded mine
+//
+// Lines are separated by a single `\n` and there is no trailing newline.
+use crate::{
+    model::{CellState, GameStatus, Minesweeper, Position},
+    snapshot::GameSnapshot,
+};
+use std::fmt::{Display, Formatter};
+
+pub const VERSION: &str = "v2";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextState {
+    pub width: u16,
+    pub height: u16,
+    pub status: GameStatus,
+    // Cells in row-major order
+    pub cells: Vec<CellState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    MissingHeader,
+    UnsupportedVersion(String),
+    InvalidHeader(String),
+    InvalidStatus(String),
+    InvalidCell { x: usize, y: usize, cell: char },
+    WrongRowLength { y: usize, length: usize },
+    WrongRowCount(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingHeader => write!(f, "missing header"),
+            ParseError::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {version}")
+            }
+            ParseError::InvalidHeader(header) => write!(f, "invalid header {header}"),
+            ParseError::InvalidStatus(status) => write!(f, "invalid status {status}"),
+            ParseError::InvalidCell { x, y, cell } => {
+                write!(f, "invalid cell {cell:?} at ({x}, {y})")
+            }
+            ParseError::WrongRowLength { y, length } => {
+                write!(f, "row {y} has {length} cells")
+            }
+            ParseError::WrongRowCount(count) => write!(f, "board has {count} rows"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl TextState {
+    pub fn new(minesweeper: &Minesweeper) -> Self {
+        Self::from(&minesweeper.snapshot())
+    }
+
+    pub fn cell(&self, (x, y): Position) -> Option<CellState> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get(y as usize * self.width as usize + x as usize)
+            .copied()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text.lines();
+
+        // Parse the header
+        let header = lines.next().ok_or(ParseError::MissingHeader)?;
+        let fields: Vec<&str> = header.split(';').collect();
+        if fields[0] != VERSION {
+            return Err(ParseError::UnsupportedVersion(fields[0].to_string()));
+        }
+        let [_, width, height, status] = fields[..] else {
+            return Err(ParseError::InvalidHeader(header.to_string()));
+        };
+        let (Ok(width), Ok(height)) = (width.parse::<u16>(), height.parse::<u16>()) else {
+            return Err(ParseError::InvalidHeader(header.to_string()));
+        };
+        let status = match status {
+            "in_progress" => GameStatus::InProgress,
+            "won" => GameStatus::Won,
+            "lost" => GameStatus::Lost,
+            _ => return Err(ParseError::InvalidStatus(status.to_string())),
+        };
+
+        // Parse the body
+        let mut cells = Vec::with_capacity(width as usize * height as usize);
+        let mut rows = 0;
+        for (y, line) in lines.enumerate() {
+            let length = line.chars().count();
+            if length != width as usize {
+                return Err(ParseError::WrongRowLength { y, length });
+            }
+            for (x, cell) in line.chars().enumerate() {
+                cells.push(match cell {
+                    '#' => CellState::Hidden,
+                    'F' => CellState::Flagged,
+                    '?' => CellState::Questioned,
+                    '0'..='8' => CellState::Open(cell as u8 - b'0'),
+                    '_' => CellState::Masked,
+                    '*' => CellState::Mine,
+                    'X' => CellState::Exploded,
+                    _ => return Err(ParseError::InvalidCell { x, y, cell }),
+                });
+            }
+            rows += 1;
+        }
+        if rows != height as usize {
+            return Err(ParseError::WrongRowCount(rows));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            status,
+            cells,
+        })
+    }
+}
+
+impl From<&GameSnapshot> for TextState {
+    fn from(snapshot: &GameSnapshot) -> Self {
+        Self {
+            width: snapshot.width(),
+            height: snapshot.height(),
+            status: snapshot.status(),
+            cells: snapshot.cells().to_vec(),
+        }
+    }
+}
+
+impl Display for TextState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = match self.status {
+            GameStatus::InProgress => "in_progress",
+            GameStatus::Won => "won",
+            GameStatus::Lost => "lost",
+        };
+        write!(f, "{VERSION};{};{};{status}", self.width, self.height)?;
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i % self.width as usize == 0 {
+                f.write_str("\n")?;
+            }
+            let cell = match cell {
+                CellState::Hidden => '#',
+                CellState::Flagged => 'F',
+                CellState::Questioned => '?',
+                CellState::Open(mines_around) => (b'0' + mines_around) as char,
+                CellState::Masked => '_',
+                CellState::Mine => '*',
+                CellState::Exploded => 'X',
+            };
+            write!(f, "{cell}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_string() {
+        let mut minesweeper = crate::board!["...F", "....", "...*"];
+        minesweeper.open((0, 1));
+
+        // ================================================
+        // Test a game in progress
+        let text = TextState::new(&minesweeper).to_string();
+        assert_eq!(text, "v2;4;3;in_progress\n001F\n002#\n001#");
+
+        // ================================================
+        // Test a lost game
+        minesweeper.open((3, 2));
+        let text = TextState::new(&minesweeper).to_string();
+        assert_eq!(text, "v2;4;3;lost\n001*\n0022\n001X");
+    }
+
+    #[test]
+    fn test_parse() {
+        // ================================================
+        // Test that a rendered board is parsed back
+        let mut minesweeper = Minesweeper::from_mines(10, 10, [(9, 0), (0, 9), (9, 9)]);
+        minesweeper.toggle_flag((0, 0));
+        minesweeper.open((5, 5));
+        minesweeper.cycle_mark((9, 0));
+        minesweeper.cycle_mark((9, 0));
+        let state = TextState::new(&minesweeper);
+        let parsed = TextState::parse(&state.to_string()).unwrap();
+        assert_eq!(parsed, state, "Round trip");
+        assert_eq!(parsed.cell((0, 0)), Some(CellState::Flagged));
+        assert_eq!(parsed.cell((9, 0)), Some(CellState::Questioned));
+        assert_eq!(parsed.cell((10, 0)), None, "Out of the board");
+
+        // ================================================
+        // Test invalid input
+        assert_eq!(TextState::parse(""), Err(ParseError::MissingHeader));
+        assert_eq!(
+            TextState::parse("v3;1;1;won\n0"),
+            Err(ParseError::UnsupportedVersion("v3".to_string()))
+        );
+        assert_eq!(
+            TextState::parse("v2;1;won\n0"),
+            Err(ParseError::InvalidHeader("v2;1;won".to_string()))
+        );
+        assert_eq!(
+            TextState::parse("v2;1;1;draw\n0"),
+            Err(ParseError::InvalidStatus("draw".to_string()))
+        );
+        assert_eq!(
+            TextState::parse("v2;2;1;won\n0!"),
+            Err(ParseError::InvalidCell {
+                x: 1,
+                y: 0,
+                cell: '!'
+            })
+        );
+        assert_eq!(
+            TextState::parse("v2;2;1;won\n000"),
+            Err(ParseError::WrongRowLength { y: 0, length: 3 })
+        );
+        assert_eq!(
+            TextState::parse("v2;2;2;won\n00"),
+            Err(ParseError::WrongRowCount(1))
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use crate::model::CellState;
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+pub const CELL: &str = "🟨";
+pub const FLAG: &str = "🇷🇺";
+pub const QUESTION: &str = "❓";
+pub const MINE: &str = "💣";
+pub const EXPLOSION: &str = "💥";
+
+// Keycap emoji for 1..=8 mines around an open cell, preceded by the given empty cell
+const fn keycaps(empty: &'static str) -> [&'static str; 9] {
+    [
+        empty,
+        "1\u{fe0f}\u{20e3}",
+        "2\u{fe0f}\u{20e3}",
+        "3\u{fe0f}\u{20e3}",
+        "4\u{fe0f}\u{20e3}",
+        "5\u{fe0f}\u{20e3}",
+        "6\u{fe0f}\u{20e3}",
+        "7\u{fe0f}\u{20e3}",
+        "8\u{fe0f}\u{20e3}",
+    ]
+}
+
+// Glyphs used to render every kind of cell on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub hidden: &'static str,
+    pub flag: &'static str,
+    pub question: &'static str,
+    pub mine: &'static str,
+    pub explosion: &'static str,
+    // Glyphs for 0..=8 mines around an open cell
+    pub numbers: [&'static str; 9],
+}
+
+impl Theme {
+    // The original look of the game: emoji cells and bare digits
+    pub const EMOJI: Theme = Theme {
+        hidden: CELL,
+        flag: FLAG,
+        question: QUESTION,
+        mine: MINE,
+        explosion: EXPLOSION,
+        numbers: ["0", "1", "2", "3", "4", "5", "6", "7", "8"],
+    };
+
+    // Keycap emoji have the same width as the other emoji, so the rows stay aligned
+    pub const KEYCAP: Theme = Theme {
+        hidden: CELL,
+        flag: FLAG,
+        question: QUESTION,
+        mine: MINE,
+        explosion: EXPLOSION,
+        numbers: keycaps("⬜"),
+    };
+
+    // Every count gets its own letter, so no distinction relies on colour
+    pub const LETTERS: Theme = Theme {
+        hidden: "#",
+        flag: "!",
+        question: "?",
+        mine: "*",
+        explosion: "X",
+        numbers: [".", "A", "B", "C", "D", "E", "F", "G", "H"],
+    };
+
+    // Black hidden cells and white open cells for the strongest contrast
+    pub const HIGH_CONTRAST: Theme = Theme {
+        hidden: "⬛",
+        flag: "🚩",
+        question: QUESTION,
+        mine: MINE,
+        explosion: EXPLOSION,
+        numbers: keycaps("⬜"),
+    };
+
+    // Raised grey buttons and a red flag like the original Windows game
+    pub const CLASSIC_WINDOWS: Theme = Theme {
+        hidden: "🔲",
+        flag: "🚩",
+        question: QUESTION,
+        mine: MINE,
+        explosion: EXPLOSION,
+        numbers: keycaps("⬜"),
+    };
+
+    // Dark cells for dark page backgrounds
+    pub const DARK: Theme = Theme {
+        hidden: "⬛",
+        flag: "🏴",
+        question: QUESTION,
+        mine: MINE,
+        explosion: EXPLOSION,
+        numbers: keycaps("🔳"),
+    };
+
+    // Plain ASCII with one character per cell, safe for terminals and <pre> blocks
+    pub const ASCII: Theme = Theme {
+        hidden: "#",
+        flag: "F",
+        question: "?",
+        mine: "*",
+        explosion: "X",
+        numbers: [".", "1", "2", "3", "4", "5", "6", "7", "8"],
+    };
+
+    pub fn number(&self, mines_around: u8) -> &'static str {
+        // We can't have more than 8 mines around a position
+        self.numbers[mines_around as usize]
+    }
+
+    pub fn glyph(&self, cell_state: CellState) -> &'static str {
+        match cell_state {
+            CellState::Hidden => self.hidden,
+            CellState::Flagged => self.flag,
+            CellState::Questioned => self.question,
+            CellState::Open(mines_around) => self.number(mines_around),
+            // A masked number looks like an open cell without mines around it
+            CellState::Masked => self.number(0),
+            CellState::Mine => self.mine,
+            CellState::Exploded => self.explosion,
+        }
+    }
+
+    // Check that every glyph is exactly one printable ASCII character, so every cell
+    // has the same visual width whatever font renders the board
+    pub fn is_monospace_safe(&self) -> bool {
+        [
+            self.hidden,
+            self.flag,
+            self.question,
+            self.mine,
+            self.explosion,
+        ]
+        .iter()
+        .chain(self.numbers.iter())
+        .all(|glyph| glyph.len() == 1 && glyph.bytes().all(|b| b.is_ascii_graphic()))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::EMOJI
+    }
+}
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemePreset {
+    Emoji,
+    Keycap,
+    Letters,
+    HighContrast,
+    Ascii,
+    ClassicWindows,
+    Dark,
+}
+
+impl ThemePreset {
+    // Registry of all built-in presets
+    pub const ALL: [ThemePreset; 7] = [
+        ThemePreset::Emoji,
+        ThemePreset::Keycap,
+        ThemePreset::Letters,
+        ThemePreset::HighContrast,
+        ThemePreset::Ascii,
+        ThemePreset::ClassicWindows,
+        ThemePreset::Dark,
+    ];
+
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemePreset::Emoji => Theme::EMOJI,
+            ThemePreset::Keycap => Theme::KEYCAP,
+            ThemePreset::Letters => Theme::LETTERS,
+            ThemePreset::HighContrast => Theme::HIGH_CONTRAST,
+            ThemePreset::Ascii => Theme::ASCII,
+            ThemePreset::ClassicWindows => Theme::CLASSIC_WINDOWS,
+            ThemePreset::Dark => Theme::DARK,
+        }
+    }
+
+    // Stable name of the preset, used to persist and select themes
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemePreset::Emoji => "emoji",
+            ThemePreset::Keycap => "keycap",
+            ThemePreset::Letters => "letters",
+            ThemePreset::HighContrast => "high-contrast",
+            ThemePreset::Ascii => "ascii",
+            ThemePreset::ClassicWindows => "classic-windows",
+            ThemePreset::Dark => "dark",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ThemePreset> {
+        ThemePreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let theme = Theme::default();
+        assert_eq!(theme.number(0), "0", "Zero is rendered as a digit");
+        assert_eq!(theme.number(8), "8", "Eight is rendered as a digit");
+
+        let theme = ThemePreset::Keycap.theme();
+        assert_eq!(theme.number(3), "3️⃣", "Three is rendered as a keycap");
+    }
+
+    #[test]
+    fn test_letters_are_distinct() {
+        // ================================================
+        // Every glyph of the letters theme must be unique to be readable without colours
+        let theme = ThemePreset::Letters.theme();
+        let mut glyphs = vec![
+            theme.hidden,
+            theme.flag,
+            theme.question,
+            theme.mine,
+            theme.explosion,
+        ];
+        glyphs.extend_from_slice(&theme.numbers);
+        let count = glyphs.len();
+        glyphs.sort();
+        glyphs.dedup();
+        assert_eq!(glyphs.len(), count, "All glyphs are distinct");
+    }
+
+    #[test]
+    fn test_from_name() {
+        // ================================================
+        // Test that every preset is found by its name
+        ThemePreset::ALL.into_iter().for_each(|preset| {
+            assert_eq!(ThemePreset::from_name(preset.name()), Some(preset));
+        });
+        assert_eq!(
+            ThemePreset::from_name("classic-windows").map(ThemePreset::theme),
+            Some(Theme::CLASSIC_WINDOWS)
+        );
+
+        // ================================================
+        // Test unknown names
+        assert_eq!(
+            ThemePreset::from_name("Dark"),
+            None,
+            "Names are case-sensitive"
+        );
+        assert_eq!(ThemePreset::from_name("neon"), None, "No such theme");
+    }
+
+    #[test]
+    fn test_is_monospace_safe() {
+        assert!(
+            Theme::ASCII.is_monospace_safe(),
+            "ASCII theme is monospace safe"
+        );
+        assert!(
+            Theme::LETTERS.is_monospace_safe(),
+            "Letters theme is monospace safe"
+        );
+        assert!(
+            !Theme::EMOJI.is_monospace_safe(),
+            "Emoji theme mixes widths"
+        );
+        assert!(
+            !Theme::KEYCAP.is_monospace_safe(),
+            "Keycaps are multi-codepoint"
+        );
+        assert!(
+            !Theme::HIGH_CONTRAST.is_monospace_safe(),
+            "High contrast uses emoji"
+        );
+    }
+}
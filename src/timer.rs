@@ -0,0 +1,255 @@
+// Game timer. It starts with the first open cell, stops once the game is won or lost and
+// can be paused in between. Undoing the end of the game starts it again and undoing the
+// first click sets it back to waiting. Every game has its own timer, which reads the time
+// from a clock that tests replace with a `MockClock`.
+use crate::{
+    clock::{Clock, SharedClock, SystemClock},
+    model::{GameStatus, Minesweeper},
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerState {
+    // Waiting for the first open cell
+    Idle,
+    Running,
+    Paused,
+    // The game is over, the time is final unless the end is undone
+    Stopped,
+}
+
+#[derive(Debug, Clone)]
+pub struct Timer<C> {
+    clock: C,
+    state: TimerState,
+    // Time counted before the current run and the start of the current run
+    counted_ms: u64,
+    running_since: u64,
+}
+
+impl<C: Clock> Timer<C> {
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            state: TimerState::Idle,
+            counted_ms: 0,
+            running_since: 0,
+        }
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        match self.state {
+            TimerState::Running => {
+                self.counted_ms + self.clock.now_ms().saturating_sub(self.running_since)
+            }
+            _ => self.counted_ms,
+        }
+    }
+
+    // Count on with another clock, keeping the time counted so far
+    pub fn set_clock(&mut self, clock: C) {
+        self.counted_ms = self.elapsed_ms();
+        self.clock = clock;
+        self.running_since = self.clock.now_ms();
+    }
+
+    // Start the timer on the first open cell and stop it when the game is over. Without
+    // an open cell, e.g. after the first click was undone, it waits again. Call it after
+    // every action, including undo and redo
+    pub fn observe(&mut self, status: GameStatus, started: bool) {
+        if !started {
+            self.reset();
+            return;
+        }
+        let is_stopped_in_game =
+            self.state == TimerState::Stopped && status == GameStatus::InProgress;
+        if is_stopped_in_game || self.state == TimerState::Idle {
+            self.state = TimerState::Running;
+            self.running_since = self.clock.now_ms();
+        }
+        if status != GameStatus::InProgress
+            && matches!(self.state, TimerState::Running | TimerState::Paused)
+        {
+            self.counted_ms = self.elapsed_ms();
+            self.state = TimerState::Stopped;
+        }
+    }
+
+    // Return false if the timer isn't running
+    pub fn pause(&mut self) -> bool {
+        if self.state != TimerState::Running {
+            return false;
+        }
+        self.counted_ms = self.elapsed_ms();
+        self.state = TimerState::Paused;
+        true
+    }
+
+    // Return false if the timer isn't paused
+    pub fn resume(&mut self) -> bool {
+        if self.state != TimerState::Paused {
+            return false;
+        }
+        self.running_since = self.clock.now_ms();
+        self.state = TimerState::Running;
+        true
+    }
+
+    // Wait for the first open cell of a new game
+    pub fn reset(&mut self) {
+        self.state = TimerState::Idle;
+        self.counted_ms = 0;
+    }
+
+    // Count on from the time of a loaded game
+    pub fn restore(&mut self, elapsed_ms: u64, status: GameStatus, started: bool) {
+        self.reset();
+        self.counted_ms = elapsed_ms;
+        self.observe(status, started);
+    }
+}
+
+impl Default for Timer<SharedClock> {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock::new()))
+    }
+}
+
+impl Minesweeper {
+    // Milliseconds played, counted from the first open cell until the game is over and
+    // without the pauses
+    pub fn elapsed_ms(&self) -> u64 {
+        self.timer.elapsed_ms()
+    }
+
+    pub fn timer_state(&self) -> TimerState {
+        self.timer.state()
+    }
+
+    // Return false if the timer isn't running
+    pub fn pause(&mut self) -> bool {
+        self.timer.pause()
+    }
+
+    // Return false if the timer isn't paused
+    pub fn resume(&mut self) -> bool {
+        self.timer.resume()
+    }
+
+    // Read the time from another clock, e.g. a `MockClock` in tests
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.timer.set_clock(clock);
+    }
+
+    pub(crate) fn observe_timer(&mut self) {
+        let started = !self.open_positions.is_empty();
+        self.timer.observe(self.status(), started);
+    }
+
+    // Count on from the time of a loaded game
+    pub(crate) fn restore_timer(&mut self, elapsed_ms: u64) {
+        let started = !self.open_positions.is_empty();
+        self.timer.restore(elapsed_ms, self.status(), started);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_timer() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let mut minesweeper = crate::board!["*..", "...", "..*"];
+        minesweeper.set_clock(clock.clone());
+
+        // ================================================
+        // Test that the timer waits for the first open cell
+        clock.advance(500);
+        minesweeper.toggle_flag((0, 0));
+        assert_eq!(minesweeper.timer_state(), TimerState::Idle);
+        assert!(!minesweeper.pause(), "Nothing to pause");
+        minesweeper.open((1, 0));
+        clock.advance(300);
+        assert_eq!(minesweeper.elapsed_ms(), 300, "Counted from the first open");
+
+        // ================================================
+        // Test pausing and resuming
+        assert!(minesweeper.pause());
+        clock.advance(10_000);
+        assert_eq!(minesweeper.elapsed_ms(), 300, "Paused");
+        assert!(!minesweeper.pause(), "Already paused");
+        assert!(minesweeper.resume());
+        assert!(!minesweeper.resume(), "Already running");
+        clock.advance(200);
+        assert_eq!(minesweeper.elapsed_ms(), 500);
+
+        // ================================================
+        // Test that the time is final once the game is over
+        minesweeper.open((2, 2));
+        clock.advance(1_000);
+        assert_eq!(minesweeper.timer_state(), TimerState::Stopped);
+        assert_eq!(minesweeper.elapsed_ms(), 500, "Stopped");
+        assert!(!minesweeper.resume(), "Game is over");
+
+        // ================================================
+        // Test that undoing the loss starts the timer again
+        minesweeper.undo();
+        assert_eq!(minesweeper.timer_state(), TimerState::Running);
+        clock.advance(100);
+        assert_eq!(minesweeper.elapsed_ms(), 600, "Counted on from the loss");
+        minesweeper.redo();
+        assert_eq!(minesweeper.timer_state(), TimerState::Stopped);
+        assert_eq!(minesweeper.elapsed_ms(), 600);
+
+        // ================================================
+        // Test that undoing the first click sets the timer back to waiting
+        minesweeper.undo();
+        minesweeper.undo();
+        assert_eq!(minesweeper.timer_state(), TimerState::Idle);
+        assert_eq!(minesweeper.elapsed_ms(), 0, "First click undone");
+        minesweeper.redo();
+        clock.advance(50);
+        assert_eq!(
+            minesweeper.elapsed_ms(),
+            50,
+            "Counted from the redone click"
+        );
+    }
+
+    #[test]
+    fn test_restore() {
+        let clock = MockClock::new(1_000);
+        let mut timer = Timer::new(&clock);
+
+        // ================================================
+        // Test that a loaded game counts on from its saved time
+        timer.restore(600, GameStatus::InProgress, true);
+        clock.advance(50);
+        assert_eq!(timer.state(), TimerState::Running);
+        assert_eq!(timer.elapsed_ms(), 650);
+
+        timer.restore(600, GameStatus::Won, true);
+        clock.advance(50);
+        assert_eq!(timer.state(), TimerState::Stopped);
+        assert_eq!(timer.elapsed_ms(), 600, "Finished game");
+
+        timer.restore(600, GameStatus::InProgress, false);
+        assert_eq!(timer.state(), TimerState::Idle);
+        assert_eq!(timer.elapsed_ms(), 0, "Nothing opened yet");
+
+        // ================================================
+        // Test that another clock keeps the time counted so far
+        let other = MockClock::new(0);
+        timer.restore(600, GameStatus::InProgress, true);
+        clock.advance(50);
+        timer.set_clock(&other);
+        other.advance(25);
+        assert_eq!(timer.elapsed_ms(), 675);
+    }
+}
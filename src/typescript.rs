@@ -0,0 +1,345 @@
+// TypeScript definitions of the JSON returned by the bindings. wasm-bindgen only knows
+// that the bindings return strings, so frontends `JSON.parse` the result and cast it to
+// the matching interface:
+//
+//     getBoardInfo         BoardInfo
+//     getGameStatus        GameStatus
+//     getGameStateJson     GameState
+//     getAnnotations       Annotation[]
+//     getAriaGrid          AriaGrid
+//     getAriaGridLocalized AriaGrid
+//     getPartition         Partition
+//     getSpectatorOverlay  (Knowledge | null)[][]
+//     getRiskMap           (Risk | null)[][]
+//     getLuckStats         LuckStats
+//     getOrientation       Orientation
+//     getOpenOrder         (number | null)[]
+//     getRegions           RegionStatus[]
+//     pointerUp            TimedAction | null
+//     getResultSummary     ResultSummary | null
+//     suggestOpening       Position | null
+//     takeFeedbackEvents   FeedbackEvent[]
+//     toggleFlag           FlagToggle | null
+//     cycleMark            FlagToggle | null
+//     probeCell            ProbeResult | null
+//     revealBoard          BoardReveal | null
+//     getThemeNames        string[]
+//     applyActions         BatchResult | null
+//     openCell             CellChange[]
+//     chordCell            CellChange[]
+//     forceOpen            CellChange[]
+//     openRegion           CellChange[]
+//     validateSubmission   Verdict | null
+//     attemptAction        Attempt | null
+//     confirmAction        CellChange[] | null
+//
+// setRegions takes a Region[] as JSON, applyActions a TimedAction[], attemptAction an
+// Action and validateSubmission a BoardReveal and a TimedAction[].
+//
+// Keep the definitions in sync with the serialized Rust types
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_os = "unknown")]
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_DEFINITIONS: &str = r#"
+export type Position = [number, number];
+
+export type GameStatus = "in_progress" | "won" | "lost";
+
+export interface BoardInfo {
+    width: number;
+    height: number;
+    mines: number;
+    status: GameStatus;
+    seed: number | null;
+    blind_mode: number | null;
+    flag_mode: boolean;
+    flag_cap: boolean;
+    safe_first_click: boolean;
+    probes_left: number;
+}
+
+export type CellView =
+    | { state: "hidden" }
+    | { state: "flagged" }
+    | { state: "questioned" }
+    | { state: "open"; mines_around: number | null }
+    | { state: "mine" }
+    | { state: "exploded" };
+
+export interface CellChange {
+    position: Position;
+    before: CellView;
+    after: CellView;
+}
+
+export interface GameState {
+    width: number;
+    height: number;
+    status: GameStatus;
+    game_over: boolean;
+    remaining_mines: number;
+    rows: CellView[][];
+    annotations: Annotation[];
+    regions: RegionStatus[];
+}
+
+export interface Annotation {
+    position: Position;
+    tag: string;
+}
+
+export interface AriaCell {
+    role: "gridcell";
+    x: number;
+    y: number;
+    label: string;
+}
+
+export interface AriaRow {
+    role: "row";
+    cells: AriaCell[];
+}
+
+export interface AriaGrid {
+    role: "grid";
+    label: string;
+    rows: AriaRow[];
+    live_message: string | null;
+}
+
+export interface FrontierComponent {
+    cells: Position[];
+    min_mines: number;
+    max_mines: number;
+    safe: Position[];
+    mines: Position[];
+    solved: boolean;
+}
+
+export type Knowledge = "safe" | "mine" | "unknown";
+
+export type Risk = "low" | "medium" | "high";
+
+export interface Partition {
+    components: FrontierComponent[];
+    sea: Position[];
+    sea_min_mines: number;
+    sea_max_mines: number;
+}
+
+export type Direction =
+    | "north"
+    | "north_east"
+    | "east"
+    | "south_east"
+    | "south"
+    | "south_west"
+    | "west"
+    | "north_west";
+
+export interface Bearing {
+    x: number;
+    y: number;
+    distance: number;
+    direction: Direction;
+    label: string;
+}
+
+export interface Orientation {
+    x: number;
+    y: number;
+    label: string;
+    neighbours: Bearing[];
+    nearest_frontier: Bearing[];
+}
+
+export interface LuckStats {
+    deductions: number;
+    guesses: number;
+    luck_ratio: number;
+}
+
+export type FeedbackEvent =
+    | { type: "SmallCascade"; cells: number }
+    | { type: "HugeCascade"; cells: number }
+    | { type: "NearMiss"; x: number; y: number; mines_around: number }
+    | { type: "Victory" }
+    | { type: "Explosion"; x: number; y: number };
+
+export type Action =
+    | { open: Position }
+    | { toggle_flag: Position }
+    | { force_open: Position }
+    | { chord: Position }
+    | { open_region: Rect }
+    | { cycle_mark: Position }
+    | { probe: Position };
+
+export interface Rect {
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export interface TimedAction {
+    action: Action;
+    time_ms: number;
+    gesture?: GestureKind | null;
+}
+
+export type GestureKind = "tap" | "long_press" | "double_tap";
+
+export type BatchResult =
+    | { status: "applied"; count: number }
+    | { status: "rolled_back"; index: number };
+
+export type CellMark = "flagged" | "questioned" | "cleared";
+
+export interface Region {
+    name: string;
+    x: number;
+    y: number;
+    width: number;
+    height: number;
+}
+
+export interface RegionStatus {
+    name: string;
+    state: "locked" | "active" | "cleared";
+}
+
+export type ProbeResult = "mine" | "safe";
+
+export interface FlagToggle {
+    mark: CellMark;
+    remaining_mines: number;
+}
+
+export interface BoardReveal {
+    width: number;
+    height: number;
+    mines: Position[];
+    nonce: string;
+}
+
+export type Verdict =
+    | { verdict: "accepted"; moves: number; time_ms: number }
+    | { verdict: "invalid_layout" }
+    | { verdict: "illegal_action"; index: number }
+    | { verdict: "too_fast"; index: number }
+    | { verdict: "not_won" }
+    | { verdict: "time_mismatch" };
+
+export type ConfirmationReason =
+    | { reason: "likely_mine"; position: Position }
+    | { reason: "wrong_flag"; position: Position };
+
+export type Attempt =
+    | { outcome: "played"; changes: CellChange[] }
+    | { outcome: "needs_confirmation"; reason: ConfirmationReason; token: number }
+    | { outcome: "debounced" };
+
+export interface ResultSummary {
+    width: number;
+    height: number;
+    mines: number;
+    status: GameStatus;
+    moves: number;
+    three_bv: number;
+    heatmap: (number | null)[][];
+    exploded: Position | null;
+    elapsed_ms: number | null;
+    three_bv_per_second: number | null;
+    probes_used: number;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aria::AriaGrid, board_info::BoardInfo, feedback::FeedbackEvent, game_state::GameState,
+        model::Minesweeper,
+    };
+    use serde::Serialize;
+
+    // The custom section is consumed by wasm-bindgen, so read the definitions from the source
+    const TYPESCRIPT_DEFINITIONS: &str = include_str!("typescript.rs");
+
+    // Check that every field of the serialized value is declared in the interface
+    fn assert_declared(interface: &str, value: &impl Serialize) {
+        let start = TYPESCRIPT_DEFINITIONS
+            .find(&format!("export interface {interface} {{"))
+            .unwrap_or_else(|| panic!("{interface} is not declared"));
+        let end = start + TYPESCRIPT_DEFINITIONS[start..].find('}').unwrap();
+        let declaration = &TYPESCRIPT_DEFINITIONS[start..end];
+
+        let json = serde_json::to_value(value).unwrap();
+        json.as_object().unwrap().keys().for_each(|key| {
+            assert!(
+                declaration.contains(&format!("    {key}: ")),
+                "{interface} lacks {key}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_definitions() {
+        let mut minesweeper = Minesweeper::from_mines(3, 3, [(0, 0)]);
+        assert_declared("BoardInfo", &BoardInfo::new(&minesweeper));
+        assert_declared("GameState", &GameState::new(&minesweeper));
+        let mut sandbox = minesweeper.sandbox();
+        let changes = sandbox.apply_with_changes(crate::action::Action::Open((2, 2)));
+        assert_declared("CellChange", &changes[0]);
+        minesweeper.annotate((0, 0), "red");
+        assert_declared("Annotation", &minesweeper.annotations()[0]);
+        assert_declared("AriaGrid", &AriaGrid::new(&minesweeper));
+        let mut frontier = Minesweeper::from_mines(3, 1, [(0, 0)]);
+        frontier.open((2, 0));
+        let partition = frontier.partition();
+        assert_declared("Partition", &partition);
+        assert_declared("FrontierComponent", &partition.components[0]);
+        assert_declared("LuckStats", &frontier.luck_stats());
+        let orientation = frontier.orientation((2, 0), 1);
+        assert_declared("Orientation", &orientation);
+        assert_declared("Bearing", &orientation.nearest_frontier[0]);
+        let region = crate::region::Region {
+            name: "all".to_string(),
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 1,
+        };
+        assert_declared("Region", &region);
+        frontier.set_regions(vec![region]).unwrap();
+        assert_declared("RegionStatus", &frontier.region_statuses()[0]);
+        assert_declared("FlagToggle", &minesweeper.toggle_flag((1, 1)).unwrap());
+
+        minesweeper.commit();
+        minesweeper.open((0, 0));
+        assert_declared("BoardReveal", &minesweeper.reveal().unwrap());
+        assert_declared("ResultSummary", &minesweeper.result_summary().unwrap());
+
+        // ================================================
+        // Test that every feedback event type is declared
+        [
+            FeedbackEvent::SmallCascade { cells: 2 },
+            FeedbackEvent::HugeCascade { cells: 20 },
+            FeedbackEvent::NearMiss {
+                x: 0,
+                y: 0,
+                mines_around: 5,
+            },
+            FeedbackEvent::Victory,
+            FeedbackEvent::Explosion { x: 0, y: 0 },
+        ]
+        .iter()
+        .for_each(|event| {
+            let json = serde_json::to_value(event).unwrap();
+            let event_type = format!(r#"type: {}"#, json["type"]);
+            assert!(TYPESCRIPT_DEFINITIONS.contains(&event_type), "{event_type}");
+        });
+    }
+}
@@ -0,0 +1,159 @@
+// Validation of submitted games, the single entry point for leaderboard backends.
+//
+// A submission is the commitment published before the game, the layout revealed after
+// it, the recorded actions and the time the player claims. The game is replayed from
+// scratch on the revealed layout, so nothing the client computed is trusted
+use crate::{
+    action::{BatchResult, TimedAction},
+    fairness::{verify_commitment, BoardReveal},
+    model::{GameStatus, Minesweeper},
+};
+use serde::Serialize;
+
+// Actions closer together than this can't be made by a human
+pub const MIN_ACTION_INTERVAL_MS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+pub enum Verdict {
+    Accepted { moves: u32, time_ms: u64 },
+    // The layout doesn't match the commitment or isn't a valid board
+    InvalidLayout,
+    // The action with the index doesn't make a move or was made earlier than the one
+    // before it
+    IllegalAction { index: usize },
+    // The action with the index was made too soon after the one before it
+    TooFast { index: usize },
+    // The replay doesn't end with a won game
+    NotWon,
+    // The claimed time is earlier than the last action
+    TimeMismatch,
+}
+
+pub fn validate_submission(
+    commitment: &str,
+    reveal: &BoardReveal,
+    replay: &[TimedAction],
+    claimed_time_ms: u64,
+) -> Verdict {
+    let mines_count = u16::try_from(reveal.mines.len()).unwrap_or(0);
+    let is_on_board = |&(x, y): &_| x < reveal.width && y < reveal.height;
+    if !Minesweeper::are_valid_parameters(reveal.width, reveal.height, mines_count)
+        || !reveal.mines.iter().all(is_on_board)
+        || !verify_commitment(commitment, reveal)
+    {
+        return Verdict::InvalidLayout;
+    }
+
+    let mut game = Minesweeper::from_mines(reveal.width, reveal.height, reveal.mines.clone());
+    if let BatchResult::RolledBack { index } = game.apply_actions(replay) {
+        return Verdict::IllegalAction { index };
+    }
+    if let Some(index) = replay
+        .windows(2)
+        .position(|pair| pair[1].time_ms - pair[0].time_ms < MIN_ACTION_INTERVAL_MS)
+    {
+        return Verdict::TooFast { index: index + 1 };
+    }
+    if game.status() != GameStatus::Won {
+        return Verdict::NotWon;
+    }
+    if replay
+        .last()
+        .is_some_and(|last| last.time_ms > claimed_time_ms)
+    {
+        return Verdict::TimeMismatch;
+    }
+
+    Verdict::Accepted {
+        moves: game.moves(),
+        time_ms: claimed_time_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+
+    fn timed(action: Action, time_ms: u64) -> TimedAction {
+        TimedAction {
+            action,
+            time_ms,
+            gesture: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_submission() {
+        //     * 1 .
+        //     1 1 .
+        //     . . .
+        let mut minesweeper = Minesweeper::from_mines(3, 3, [(0, 0)]);
+        let commitment = minesweeper.commit();
+        minesweeper.open((2, 2));
+        let reveal = minesweeper.reveal().unwrap();
+        let replay = [timed(Action::Open((2, 2)), 500)];
+
+        // ================================================
+        // Test a valid submission
+        assert_eq!(
+            validate_submission(&commitment, &reveal, &replay, 800),
+            Verdict::Accepted {
+                moves: 1,
+                time_ms: 800
+            }
+        );
+
+        // ================================================
+        // Test that the layout must match the commitment and be a valid board
+        let mut moved = reveal.clone();
+        moved.mines = vec![(1, 0)];
+        assert_eq!(
+            validate_submission(&commitment, &moved, &replay, 800),
+            Verdict::InvalidLayout
+        );
+        let mut outside = reveal.clone();
+        outside.mines = vec![(3, 0)];
+        let commitment_outside = outside.commitment();
+        assert_eq!(
+            validate_submission(&commitment_outside, &outside, &replay, 800),
+            Verdict::InvalidLayout,
+            "Mine outside the board"
+        );
+
+        // ================================================
+        // Test that the replay must be legal, humanly possible and win the game
+        let replay = [
+            timed(Action::ToggleFlag((0, 0)), 100),
+            timed(Action::Open((1, 0)), 300),
+            timed(Action::Chord((1, 0)), 310),
+        ];
+        assert_eq!(
+            validate_submission(&commitment, &reveal, &replay, 800),
+            Verdict::TooFast { index: 2 }
+        );
+        let replay = [
+            timed(Action::Open((1, 0)), 100),
+            timed(Action::Open((1, 0)), 300),
+        ];
+        assert_eq!(
+            validate_submission(&commitment, &reveal, &replay, 800),
+            Verdict::IllegalAction { index: 1 },
+            "Cell already open"
+        );
+        let replay = [timed(Action::Open((1, 0)), 100)];
+        assert_eq!(
+            validate_submission(&commitment, &reveal, &replay, 800),
+            Verdict::NotWon
+        );
+
+        // ================================================
+        // Test that the claimed time covers the replay
+        let replay = [timed(Action::Open((2, 2)), 500)];
+        assert_eq!(
+            validate_submission(&commitment, &reveal, &replay, 400),
+            Verdict::TimeMismatch
+        );
+    }
+}
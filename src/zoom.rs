@@ -0,0 +1,96 @@
+// Zoomed text rendering: every cell becomes a square block of glyph slots, for terminals
+// with small fonts. Hidden, flagged and mine cells keep the hidden background, open
+// cells get the background of an empty open cell. The glyph of the cell sits in the
+// center slot of the block, or in the top-left one of the 2x2 block which has no center.
+// Every slot is rendered like a cell of the regular board, so the normal zoom matches
+// `Display`.
+use crate::model::{CellState, Minesweeper};
+use std::fmt::Write;
+#[cfg(target_os = "unknown")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(target_os = "unknown", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zoom {
+    Normal,
+    Double,
+    Triple,
+}
+
+impl Zoom {
+    // Side of a cell block in glyph slots
+    pub fn scale(self) -> u16 {
+        match self {
+            Zoom::Normal => 1,
+            Zoom::Double => 2,
+            Zoom::Triple => 3,
+        }
+    }
+}
+
+impl Minesweeper {
+    pub fn render_zoomed(&self, zoom: Zoom) -> String {
+        let scale = zoom.scale();
+        let center = (scale - 1) / 2;
+        let theme = self.theme();
+
+        let mut rendered = String::new();
+        for y in 0..self.height() {
+            for row in 0..scale {
+                for x in 0..self.width() {
+                    let cell_state = self.cell_state((x, y));
+                    let background = match cell_state {
+                        CellState::Hidden
+                        | CellState::Flagged
+                        | CellState::Questioned
+                        | CellState::Mine => theme.glyph(CellState::Hidden),
+                        _ => theme.number(0),
+                    };
+                    for column in 0..scale {
+                        let glyph = if (column, row) == (center, center) {
+                            theme.glyph(cell_state)
+                        } else {
+                            background
+                        };
+                        // Writing to a string can't fail
+                        let _ = write!(rendered, "{glyph} ");
+                    }
+                }
+                rendered.push('\n');
+            }
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_render_zoomed() {
+        let mut minesweeper = crate::board!["*.", ".."];
+        minesweeper.set_theme(Theme::ASCII);
+        minesweeper.toggle_flag((0, 0));
+        minesweeper.open((1, 1));
+        assert_eq!(
+            minesweeper.render_zoomed(Zoom::Normal),
+            minesweeper.to_string(),
+            "Normal zoom is the regular board"
+        );
+
+        // ================================================
+        // Test the 2x2 and 3x3 blocks
+        assert_eq!(
+            minesweeper.render_zoomed(Zoom::Double),
+            "F # # # \n# # # # \n# # 1 . \n# # . . \n"
+        );
+        let rendered = minesweeper.render_zoomed(Zoom::Triple);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 6, "3 lines per row");
+        assert_eq!(lines[1], "# F # # # # ");
+        assert_eq!(lines[4], "# # # . 1 . ");
+        assert_eq!(lines[5], "# # # . . . ");
+    }
+}